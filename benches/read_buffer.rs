@@ -0,0 +1,69 @@
+//! Compare allocating the read buffer on every iteration of the read loop with reusing one.
+//!
+//! The source delivers one second of data at 115200 baud (8N1, 11520 bytes) in reads of at most
+//! `READ_SIZE` bytes, the same way the loop started by `read` drains a port.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::io::{self, Read};
+
+const BAUD_RATE: usize = 115_200;
+/// 8 data bits plus a start and a stop bit per byte.
+const BYTES_PER_SECOND: usize = BAUD_RATE / 10;
+const READ_SIZE: usize = 1024;
+
+/// A port that has `remaining` bytes waiting and returns them in small bursts.
+struct Port {
+    remaining: usize,
+}
+
+impl Read for Port {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = buf.len().min(self.remaining).min(64);
+        buf[..size].fill(0x55);
+        self.remaining -= size;
+        Ok(size)
+    }
+}
+
+fn allocate_per_read(port: &mut Port) -> usize {
+    let mut total = 0;
+    while port.remaining > 0 {
+        let mut serial_buf: Vec<u8> = vec![0; READ_SIZE];
+        let size = port.read(serial_buf.as_mut_slice()).unwrap();
+        total += black_box(&serial_buf[..size]).len();
+    }
+    total
+}
+
+fn reuse_buffer(port: &mut Port) -> usize {
+    let mut total = 0;
+    let mut serial_buf: Vec<u8> = vec![0; READ_SIZE];
+    while port.remaining > 0 {
+        let size = port.read(serial_buf.as_mut_slice()).unwrap();
+        total += black_box(&serial_buf[..size]).len();
+    }
+    total
+}
+
+fn read_buffer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_115200_baud");
+    group.throughput(Throughput::Bytes(BYTES_PER_SECOND as u64));
+    group.bench_function(BenchmarkId::new("allocate_per_read", READ_SIZE), |b| {
+        b.iter(|| {
+            allocate_per_read(&mut Port {
+                remaining: BYTES_PER_SECOND,
+            })
+        })
+    });
+    group.bench_function(BenchmarkId::new("reuse_buffer", READ_SIZE), |b| {
+        b.iter(|| {
+            reuse_buffer(&mut Port {
+                remaining: BYTES_PER_SECOND,
+            })
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, read_buffer);
+criterion_main!(benches);
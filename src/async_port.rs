@@ -0,0 +1,48 @@
+use crate::command::{get_port_builder, stop_read};
+use crate::error::Error;
+use crate::state::SerialportState;
+use std::sync::Arc;
+use tokio_crate::sync::Mutex;
+use tokio_serial::{SerialPortBuilderExt, SerialStream};
+
+/// Get an async handle of the open port at `path`, for Rust code using Tokio I/O on it.
+///
+/// The first call hands the port over: it stops any read loop, removes the port from the
+/// commands and opens it again as a `SerialStream` with the same settings. Later calls return
+/// the same stream. Must be called from within a Tokio runtime, e.g. an async Tauri command.
+pub fn get_async_port(
+    state: &SerialportState,
+    path: &str,
+) -> Result<Arc<Mutex<SerialStream>>, Error> {
+    let mut async_ports = state.async_ports.lock().map_err(|_| Error::LockPoisoned)?;
+    if let Some(port) = async_ports.get(path) {
+        return Ok(port.clone());
+    }
+    let mut serialport_info = match state.serialports.lock() {
+        Ok(mut serialports) => match serialports.remove(path) {
+            Some(serialport_info) => serialport_info,
+            None => return Err(Error::PortNotFound(path.to_string())),
+        },
+        Err(_) => return Err(Error::LockPoisoned),
+    };
+    let sender = serialport_info.sender.take();
+    let read_task = serialport_info.read_task.take();
+    let config = serialport_info.config.clone();
+    // the port is opened exclusively, so every handle has to be closed before reopening it
+    drop(serialport_info);
+    stop_read(path, sender, read_task)?;
+    match get_port_builder(path, &config)?.open_native_async() {
+        Ok(stream) => {
+            let port = Arc::new(Mutex::new(stream));
+            async_ports.insert(path.to_string(), port.clone());
+            Ok(port)
+        }
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error opening {} for async use: {}",
+                path, error.description
+            ),
+        )),
+    }
+}
@@ -0,0 +1,130 @@
+use crate::error::Error;
+#[cfg(any(target_os = "linux", windows))]
+use std::{io, net::TcpStream};
+
+/// Parse a Bluetooth device address written as six hex bytes, e.g. `00:11:22:AA:BB:CC`.
+///
+/// The bytes are returned most significant first, as written.
+pub(crate) fn parse_bluetooth_address(address: &str) -> Result<[u8; 6], Error> {
+    let invalid = || Error::InvalidArgument(format!("Invalid Bluetooth address {}", address));
+    let mut bytes = [0; 6];
+    let mut parts = address.split(|c| c == ':' || c == '-');
+    for byte in &mut bytes {
+        let part = parts.next().ok_or_else(invalid)?;
+        if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        *byte = u8::from_str_radix(part, 16).map_err(|_| invalid())?;
+    }
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok(bytes)
+}
+
+/// Connect to RFCOMM `channel` of the device at `address`.
+///
+/// RFCOMM sockets are stream sockets like TCP ones, so the connected socket is handed out as a
+/// `TcpStream`, whose reads, writes and timeouts only use calls common to all sockets.
+#[cfg(target_os = "linux")]
+pub(crate) fn connect(address: [u8; 6], channel: u8) -> io::Result<TcpStream> {
+    use nix::libc;
+    use std::os::unix::io::FromRawFd;
+
+    const BTPROTO_RFCOMM: libc::c_int = 3;
+
+    /// `struct sockaddr_rc` of BlueZ.
+    #[repr(C)]
+    struct SockaddrRc {
+        rc_family: libc::sa_family_t,
+        rc_bdaddr: [u8; 6],
+        rc_channel: u8,
+    }
+
+    // SAFETY: creates a new socket, the result is checked below
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_BLUETOOTH,
+            libc::SOCK_STREAM | libc::SOCK_CLOEXEC,
+            BTPROTO_RFCOMM,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: nothing else owns `fd`, the stream closes it when dropped
+    let stream = unsafe { TcpStream::from_raw_fd(fd) };
+    let mut rc_bdaddr = address;
+    // BlueZ stores addresses least significant byte first
+    rc_bdaddr.reverse();
+    let addr = SockaddrRc {
+        rc_family: libc::AF_BLUETOOTH as libc::sa_family_t,
+        rc_bdaddr,
+        rc_channel: channel,
+    };
+    // SAFETY: `addr` outlives the call and its size is passed along
+    let result = unsafe {
+        libc::connect(
+            fd,
+            &addr as *const SockaddrRc as *const libc::sockaddr,
+            std::mem::size_of::<SockaddrRc>() as libc::socklen_t,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stream)
+}
+
+/// Connect to RFCOMM `channel` of the device at `address`.
+///
+/// RFCOMM sockets are stream sockets like TCP ones, so the connected socket is handed out as a
+/// `TcpStream`, whose reads, writes and timeouts only use calls common to all sockets.
+#[cfg(windows)]
+pub(crate) fn connect(address: [u8; 6], channel: u8) -> io::Result<TcpStream> {
+    use std::os::windows::io::{FromRawSocket, RawSocket};
+    use windows_sys::Win32::{
+        Devices::Bluetooth::{AF_BTH, BTHPROTO_RFCOMM, SOCKADDR_BTH},
+        Networking::WinSock::{
+            connect, socket, WSAGetLastError, WSAStartup, INVALID_SOCKET, SOCKADDR, SOCKET_ERROR,
+            SOCK_STREAM, WSADATA,
+        },
+    };
+
+    // SAFETY: only reads the last error of the calling thread
+    let last_error = || io::Error::from_raw_os_error(unsafe { WSAGetLastError() });
+    // Winsock counts its initializations, so this only makes sure it is ready before the
+    // first socket of the process
+    // SAFETY: all zeros is a valid `WSADATA`, and it outlives the call that fills it
+    let mut data: WSADATA = unsafe { std::mem::zeroed() };
+    let result = unsafe { WSAStartup(0x202, &mut data) };
+    if result != 0 {
+        return Err(io::Error::from_raw_os_error(result));
+    }
+    // SAFETY: creates a new socket, the result is checked below
+    let raw = unsafe { socket(AF_BTH as i32, SOCK_STREAM, BTHPROTO_RFCOMM as i32) };
+    if raw == INVALID_SOCKET {
+        return Err(last_error());
+    }
+    // SAFETY: nothing else owns `raw`, the stream closes it when dropped
+    let stream = unsafe { TcpStream::from_raw_socket(raw as RawSocket) };
+    // SAFETY: all zeros is a valid `SOCKADDR_BTH`, the fields in use are set below
+    let mut addr: SOCKADDR_BTH = unsafe { std::mem::zeroed() };
+    addr.addressFamily = AF_BTH;
+    addr.btAddr = address
+        .iter()
+        .fold(0, |addr, &byte| (addr << 8) | u64::from(byte));
+    addr.port = u32::from(channel);
+    // SAFETY: `addr` outlives the call and its size is passed along
+    let result = unsafe {
+        connect(
+            raw,
+            &addr as *const SOCKADDR_BTH as *const SOCKADDR,
+            std::mem::size_of::<SOCKADDR_BTH>() as i32,
+        )
+    };
+    if result == SOCKET_ERROR {
+        return Err(last_error());
+    }
+    Ok(stream)
+}
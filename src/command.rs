@@ -1,14 +1,34 @@
+use crate::bluetooth::parse_bluetooth_address;
+use crate::crc::Crc;
 use crate::error::Error;
-use crate::state::{ReadData, SerialportInfo, SerialportState};
-// use std::collections::HashMap;
-use serialport::{DataBits, FlowControl, Parity, StopBits};
+use crate::framing::{cobs_encode, Framer};
+use crate::peek::{self, PeekBuffer, PeekablePort};
+use crate::pool::ThreadPool;
+use crate::recorder::{Recorder, RecordingPort};
+use crate::state::{
+    AccessMode, ErrorData, Lease, LeaseToken, NmeaData, OpenParams, OverflowData, PortEvent,
+    PortFilter, PortInfo, QueuedWrites, ReadData, ReadEncoding, ReadOptions, ReadPayload,
+    ReconnectData, ReconnectOptions, SerialPortDetail, SerialportConfig, SerialportCounters,
+    SerialportInfo, SerialportPluginConfig, SerialportState, SerialportStats, SignalState,
+    SilenceData, WriteQueue,
+};
+use crate::tcp::TcpSerialPort;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use crossbeam_channel::{TryRecvError, TrySendError};
+use serialport::{
+    ClearBuffer, DataBits, FlowControl, Parity, SerialPort, SerialPortBuilder, StopBits,
+};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::mpsc;
-use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
-use std::time::Duration;
-use tauri::{command, AppHandle, Runtime, State, Window};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{command, AppHandle, Manager, Runtime, State, Window};
 
-/// `get_worksheet` according to `path` and `sheet_name` get the file sheet instance
+/// `get_serialport` look up the open serial port at `path` and run `f` on it
 fn get_serialport<T, F: FnOnce(&mut SerialportInfo) -> Result<T, Error>>(
     state: State<'_, SerialportState>,
     path: String,
@@ -17,30 +37,261 @@ fn get_serialport<T, F: FnOnce(&mut SerialportInfo) -> Result<T, Error>>(
     match state.serialports.lock() {
         Ok(mut map) => match map.get_mut(&path) {
             Some(serialport_info) => f(serialport_info),
-            None => {
-                Err(Error::String("Serial port not found".to_string()))
-            }
+            None => Err(Error::PortNotFound(path)),
         },
-        Err(error) =>  Err(Error::String(format!("Failed to get file lock! {} ", error))),
-    }
-}
-
-/// `get_worksheet` according to `path` and `sheet_name` get the file sheet instance
-// fn try_get_serialport<T, F: FnOnce(&mut SerialportInfo) -> Result<T, Error>>(
-//     state: Arc<std::sync::Mutex<HashMap<std::string::String, SerialportInfo>>>,
-//     path: String,
-//     f: F,
-// ) -> Result<T, Error> {
-//     match state.try_lock() {
-//         Ok(mut map) => match map.get_mut(&path) {
-//             Some(serialport_info) => return f(serialport_info),
-//             None => {
-//                 return Err(Error::String(format!("Serial port not found {} 串口", &path)));
-//             }
-//         },
-//         Err(error) => return Err(Error::String(format!("Failed to get file lock! {} ", error))),
-//     }
-// }
+        Err(_) => Err(Error::LockPoisoned),
+    }
+}
+
+/// `write_bytes` write `data` to the port once, failing with `PartialWrite` if the port
+/// accepted only part of it
+fn write_bytes(
+    serialport_info: &mut SerialportInfo,
+    path: &str,
+    data: &[u8],
+) -> Result<usize, Error> {
+    check_writable(serialport_info, path)?;
+    match serialport_info.serialport.write(data) {
+        Ok(size) => {
+            serialport_info.counters.record_write(size);
+            if serialport_info.hex_dump.load(Ordering::Relaxed) {
+                print_hex_dump(path, "wrote", &data[..size]);
+            }
+            if size < data.len() {
+                return Err(Error::PartialWrite {
+                    written: size,
+                    total: data.len(),
+                });
+            }
+            Ok(size)
+        }
+        Err(error) => Err(Error::IoError(format!(
+            "Error writing to serial port {}: {}",
+            path, error
+        ))),
+    }
+}
+
+/// `check_writable` fail with `AccessDenied` unless the port was opened for writing
+fn check_writable(serialport_info: &SerialportInfo, path: &str) -> Result<(), Error> {
+    if serialport_info.access_mode.can_write() {
+        Ok(())
+    } else {
+        Err(Error::AccessDenied {
+            path: path.to_string(),
+            operation: "writing",
+        })
+    }
+}
+
+/// `write_all_bytes` keep writing `data` until all of it has been accepted by the port,
+/// giving up after `max_attempts` write calls when it is set
+fn write_all_bytes(
+    serialport_info: &mut SerialportInfo,
+    path: &str,
+    data: &[u8],
+    max_attempts: Option<u32>,
+) -> Result<(), Error> {
+    check_writable(serialport_info, path)?;
+    let mut remaining = data;
+    let mut attempts: u32 = 0;
+    while !remaining.is_empty() {
+        if let Some(max_attempts) = max_attempts {
+            if attempts >= max_attempts {
+                return Err(Error::IoError(format!(
+                    "Error writing to serial port {}: only {} of {} bytes written after {} attempts",
+                    path,
+                    data.len() - remaining.len(),
+                    data.len(),
+                    attempts
+                )));
+            }
+        }
+        attempts += 1;
+        match serialport_info.serialport.write(remaining) {
+            Ok(0) => {
+                return Err(Error::IoError(format!(
+                    "Error writing to serial port {}: port accepted no data",
+                    path
+                )));
+            }
+            Ok(size) => {
+                serialport_info.counters.record_write(size);
+                if serialport_info.hex_dump.load(Ordering::Relaxed) {
+                    print_hex_dump(path, "wrote", &remaining[..size]);
+                }
+                remaining = &remaining[size..];
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(error) => {
+                return Err(Error::IoError(format!(
+                    "Error writing to serial port {}: {}",
+                    path, error
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `flush_port` wait until the OS has transmitted everything written to the port
+fn flush_port(serialport_info: &mut SerialportInfo, path: &str) -> Result<(), Error> {
+    match serialport_info.serialport.flush() {
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::IoError(format!(
+            "Error flushing serial port {}: {}",
+            path, error
+        ))),
+    }
+}
+
+/// `decode_hex` decode a hex string such as `"A5 01 FF"` or `"a501ff"` into bytes
+fn decode_hex(value: &str) -> Result<Vec<u8>, Error> {
+    let digits: Vec<u8> = value
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    if digits.len() % 2 != 0 {
+        return Err(Error::InvalidArgument(format!(
+            "Invalid hex string {:?}: odd number of digits",
+            value
+        )));
+    }
+    let mut bytes = Vec::with_capacity(digits.len() / 2);
+    for pair in digits.chunks(2) {
+        let pair = std::str::from_utf8(pair).unwrap_or_default();
+        match u8::from_str_radix(pair, 16) {
+            Ok(byte) => bytes.push(byte),
+            Err(_) => {
+                return Err(Error::InvalidArgument(format!(
+                    "Invalid hex string {:?}: {:?} is not a hex byte",
+                    value, pair
+                )));
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// `encode_text` encode `value` in the charset named by the IANA label `encoding`
+///
+/// `latin-1` (`iso-8859-1`) and `ascii` map code points to bytes directly, other labels are
+/// encoded with `encoding_rs`, which like browsers treats most of them as `windows-1252` and
+/// encodes UTF-16 as UTF-8. Unmappable characters fail with `InvalidArgument` when `strict`,
+/// otherwise they are written as `?`.
+pub(crate) fn encode_text(value: &str, encoding: &str, strict: bool) -> Result<Vec<u8>, Error> {
+    let unmappable = |c: char| {
+        Error::InvalidArgument(format!(
+            "Character {:?} cannot be encoded as {}",
+            c, encoding
+        ))
+    };
+    let max_code_point = match encoding.to_ascii_lowercase().as_str() {
+        "latin-1" | "latin1" | "iso-8859-1" => Some(0xff),
+        "ascii" | "us-ascii" => Some(0x7f),
+        _ => None,
+    };
+    if let Some(max_code_point) = max_code_point {
+        return value
+            .chars()
+            .map(|c| match c as u32 {
+                code_point if code_point <= max_code_point => Ok(code_point as u8),
+                _ if strict => Err(unmappable(c)),
+                _ => Ok(b'?'),
+            })
+            .collect();
+    }
+    let mut encoder = encoding_rs::Encoding::for_label(encoding.as_bytes())
+        .ok_or_else(|| Error::InvalidArgument(format!("Unknown encoding {:?}", encoding)))?
+        .output_encoding()
+        .new_encoder();
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut remaining = value;
+    loop {
+        let (result, read) =
+            encoder.encode_from_utf8_to_vec_without_replacement(remaining, &mut bytes, true);
+        remaining = &remaining[read..];
+        match result {
+            encoding_rs::EncoderResult::InputEmpty => return Ok(bytes),
+            encoding_rs::EncoderResult::OutputFull => bytes.reserve(
+                encoder
+                    .max_buffer_length_from_utf8_without_replacement(remaining.len())
+                    .unwrap_or(remaining.len() * 4),
+            ),
+            encoding_rs::EncoderResult::Unmappable(c) if strict => return Err(unmappable(c)),
+            encoding_rs::EncoderResult::Unmappable(_) => bytes.push(b'?'),
+        }
+    }
+}
+
+/// `timestamp_ms` current Unix time in milliseconds
+pub(crate) fn timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// `sleep` wait for `duration` on a blocking thread, so async commands do not hold up the runtime
+async fn sleep(duration: Duration) {
+    let _ = tauri::async_runtime::spawn_blocking(move || thread::sleep(duration)).await;
+}
+
+/// `encode_hex` encode bytes as a lowercase hex string
+fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// `format_hex_dump` format bytes 16 to a line as their offset, hex and ASCII, like
+/// `0000: 48 65 6C 6C 6F  Hello`, showing unprintable bytes as `.`
+pub(crate) fn format_hex_dump(data: &[u8]) -> String {
+    // short dumps are not padded, longer ones line up their ASCII columns
+    let width = (data.len().min(16) * 3).saturating_sub(1);
+    data.chunks(16)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02X}", byte)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!(
+                "{:04X}: {:<width$}  {}",
+                line * 16,
+                hex.join(" "),
+                ascii,
+                width = width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `print_hex_dump` log the bytes the port at `path` read or wrote, see `set_hex_dump`
+fn print_hex_dump(path: &str, action: &str, data: &[u8]) {
+    info!(
+        "Serial port {} {} {} bytes:\n{}",
+        path,
+        action,
+        data.len(),
+        format_hex_dump(data)
+    );
+}
+
+/// `encode_read_data` serialize received bytes according to the requested `read_encoding`
+fn encode_read_data(encoding: ReadEncoding, data: &[u8]) -> ReadPayload {
+    match encoding {
+        ReadEncoding::Raw => ReadPayload::Raw(data.to_vec()),
+        ReadEncoding::Hex => ReadPayload::Encoded(encode_hex(data)),
+        ReadEncoding::Base64 => ReadPayload::Encoded(BASE64.encode(data)),
+    }
+}
 
 fn get_data_bits(value: Option<usize>) -> DataBits {
     match value {
@@ -55,326 +306,3921 @@ fn get_data_bits(value: Option<usize>) -> DataBits {
     }
 }
 
-fn get_flow_control(value: Option<String>) -> FlowControl {
-    match value {
-        Some(value) => match value.as_str() {
-            "Software" => FlowControl::Software,
-            "Hardware" => FlowControl::Hardware,
-            _ => FlowControl::None,
-        },
-        None => FlowControl::None,
-    }
+pub(crate) fn get_flow_control(value: Option<String>) -> Result<FlowControl, Error> {
+    match value {
+        Some(value) => match value.to_lowercase().as_str() {
+            "none" => Ok(FlowControl::None),
+            "software" => Ok(FlowControl::Software),
+            "hardware" => Ok(FlowControl::Hardware),
+            _ => Err(Error::InvalidArgument(format!(
+                "Invalid flow control {:?}, expected \"None\", \"Software\" or \"Hardware\"",
+                value
+            ))),
+        },
+        None => Ok(FlowControl::None),
+    }
+}
+
+pub(crate) fn get_parity(value: Option<String>) -> Result<Parity, Error> {
+    match value {
+        Some(value) => match value.to_lowercase().as_str() {
+            "none" => Ok(Parity::None),
+            "odd" => Ok(Parity::Odd),
+            "even" => Ok(Parity::Even),
+            _ => Err(Error::InvalidArgument(format!(
+                "Invalid parity {:?}, expected \"None\", \"Odd\" or \"Even\"",
+                value
+            ))),
+        },
+        None => Ok(Parity::None),
+    }
+}
+
+pub(crate) fn get_access_mode(value: Option<String>) -> Result<AccessMode, Error> {
+    match value {
+        Some(value) => match value.as_str() {
+            "read_only" => Ok(AccessMode::ReadOnly),
+            "write_only" => Ok(AccessMode::WriteOnly),
+            "read_write" => Ok(AccessMode::ReadWrite),
+            _ => Err(Error::InvalidArgument(format!(
+                "Invalid access mode {:?}, expected \"read_only\", \"write_only\" or \"read_write\"",
+                value
+            ))),
+        },
+        None => Ok(AccessMode::ReadWrite),
+    }
+}
+
+pub(crate) fn get_stop_bits(value: Option<usize>) -> StopBits {
+    match value {
+        Some(value) => match value {
+            1 => StopBits::One,
+            2 => StopBits::Two,
+            _ => StopBits::One,
+        },
+        None => StopBits::One,
+    }
+}
+
+/// Baud rates accepted by `open` when `validate_baud_rate` is set, tried by `supported_baud_rates`.
+const STANDARD_BAUD_RATES: [u32; 24] = [
+    50, 75, 110, 134, 150, 200, 300, 600, 1200, 1800, 2400, 4800, 9600, 19200, 38400, 57600,
+    115200, 230400, 250000, 460800, 500000, 921600, 1000000, 1500000,
+];
+
+/// `check_baud_rate` fail unless `baud_rate` is one of `STANDARD_BAUD_RATES`
+fn check_baud_rate(baud_rate: u32) -> Result<(), Error> {
+    if STANDARD_BAUD_RATES.contains(&baud_rate) {
+        Ok(())
+    } else {
+        Err(Error::InvalidArgument(format!(
+            "Non-standard baud rate {}, open with validate_baud_rate false to use it anyway",
+            baud_rate
+        )))
+    }
+}
+
+/// `check_allowed_port` fail with `PermissionDenied` unless `path` is in `allowed_ports`
+///
+/// Unset or empty `allowed_ports` allow every port. With the `regex` feature every entry is a
+/// regular expression that has to match the whole path, otherwise the path has to equal an entry.
+pub(crate) fn check_allowed_port(config: &SerialportPluginConfig, path: &str) -> Result<(), Error> {
+    let allowed_ports = match &config.allowed_ports {
+        Some(allowed_ports) if !allowed_ports.is_empty() => allowed_ports,
+        _ => return Ok(()),
+    };
+    for allowed_port in allowed_ports {
+        if port_matches(allowed_port, path)? {
+            return Ok(());
+        }
+    }
+    Err(Error::PermissionDenied(path.to_string()))
+}
+
+/// `check_window_access` fail with `PermissionDenied` unless `window_port_acl` lets the window
+/// labelled `window_label` use `path`
+///
+/// Unset `window_port_acl` lets every window use every port. Otherwise only windows listed in it
+/// have access, to the ports matching their entries like `allowed_ports`, or all with `"*"`.
+pub(crate) fn check_window_access(
+    config: &SerialportPluginConfig,
+    window_label: &str,
+    path: &str,
+) -> Result<(), Error> {
+    let window_port_acl = match &config.window_port_acl {
+        Some(window_port_acl) => window_port_acl,
+        None => return Ok(()),
+    };
+    for pattern in window_port_acl.get(window_label).into_iter().flatten() {
+        if pattern == "*" || port_matches(pattern, path)? {
+            return Ok(());
+        }
+    }
+    Err(Error::PermissionDenied(path.to_string()))
+}
+
+/// `window_ports` the `paths` that `window_port_acl` lets `window` use
+fn window_ports<'a, R: Runtime>(
+    config: &SerialportPluginConfig,
+    window: &Window<R>,
+    paths: impl Iterator<Item = &'a String>,
+) -> Vec<String> {
+    paths
+        .filter(|path| check_window_access(config, window.label(), path).is_ok())
+        .cloned()
+        .collect()
+}
+
+/// `port_matches` whether `path` matches an entry of the plugin configuration, a regular
+/// expression matching the whole path with the `regex` feature or the path itself otherwise
+fn port_matches(pattern: &str, path: &str) -> Result<bool, Error> {
+    #[cfg(feature = "regex")]
+    {
+        let regex = regex::Regex::new(&format!("^(?:{})$", pattern)).map_err(|error| {
+            Error::InvalidArgument(format!("Invalid port pattern {}: {}", pattern, error))
+        })?;
+        Ok(regex.is_match(path))
+    }
+    #[cfg(not(feature = "regex"))]
+    Ok(pattern == path)
+}
+
+/// `get_framer` the framer of a `read` loop
+///
+/// `framing` names a protocol the messages are decoded with, `"slip"`, `"cobs"`,
+/// `"length_prefix"`, configured by `length_field_size` and `endian`, `"modbus_rtu"`, which
+/// splits frames at the silent interval of the port's `baud_rate`, or `"nmea"` for NMEA 0183
+/// sentences.
+fn get_framer(options: &ReadOptions, baud_rate: u32) -> Result<Framer, Error> {
+    let delimiter = options.delimiter.clone();
+    let packet_size = options.packet_size;
+    if options.framing.as_deref() != Some("length_prefix")
+        && (options.length_field_size.is_some() || options.endian.is_some())
+    {
+        return Err(Error::InvalidArgument(
+            "length_field_size and endian only apply to length_prefix framing!".to_string(),
+        ));
+    }
+    if let Some(framing) = &options.framing {
+        if delimiter.is_some() || packet_size.is_some() {
+            return Err(Error::InvalidArgument(
+                "Framing cannot be combined with delimiter or packet_size!".to_string(),
+            ));
+        }
+        return match framing.as_str() {
+            "slip" => Ok(Framer::Slip {
+                buffer: vec![],
+                escaped: false,
+                invalid: false,
+                errors: vec![],
+            }),
+            "cobs" => Ok(Framer::Cobs {
+                buffer: vec![],
+                errors: vec![],
+            }),
+            "length_prefix" => Ok(Framer::LengthPrefix {
+                field_size: match options.length_field_size.unwrap_or(2) {
+                    size @ (1 | 2 | 4) => size as usize,
+                    size => {
+                        return Err(Error::InvalidArgument(format!(
+                            "Invalid length field size {}, expected 1, 2 or 4",
+                            size
+                        )))
+                    }
+                },
+                big_endian: match options.endian.as_deref().unwrap_or("big") {
+                    "big" => true,
+                    "little" => false,
+                    endian => {
+                        return Err(Error::InvalidArgument(format!(
+                            "Invalid endian {:?}, expected \"big\" or \"little\"",
+                            endian
+                        )))
+                    }
+                },
+                buffer: vec![],
+            }),
+            "modbus_rtu" => Ok(Framer::ModbusRtu {
+                silence: modbus_silence(baud_rate),
+                buffer: vec![],
+                last_read_at: None,
+                errors: vec![],
+            }),
+            "nmea" => Ok(Framer::Nmea {
+                buffer: vec![],
+                errors: vec![],
+            }),
+            _ => Err(Error::InvalidArgument(format!(
+                "Invalid framing {:?}, expected \"slip\", \"cobs\", \"length_prefix\", \"modbus_rtu\" or \"nmea\"",
+                framing
+            ))),
+        };
+    }
+    match (delimiter, packet_size) {
+        (Some(_), Some(_)) => Err(Error::InvalidArgument(
+            "Only one of delimiter and packet_size can be set!".to_string(),
+        )),
+        (Some(delimiter), None) if delimiter.is_empty() => Err(Error::InvalidArgument(
+            "Delimiter cannot be empty!".to_string(),
+        )),
+        (Some(delimiter), None) => Ok(Framer::Delimiter {
+            delimiter,
+            buffer: vec![],
+        }),
+        (None, Some(0)) => Err(Error::InvalidArgument(
+            "Packet size cannot be 0!".to_string(),
+        )),
+        (None, Some(size)) => Ok(Framer::PacketSize {
+            size,
+            buffer: Vec::with_capacity(size * 2),
+        }),
+        (None, None) => Ok(Framer::None),
+    }
+}
+
+/// `modbus_silence` the silent interval of 3.5 characters that ends a MODBUS RTU frame
+///
+/// Above 19200 baud the MODBUS specification fixes it at 1.75 ms.
+fn modbus_silence(baud_rate: u32) -> Duration {
+    if baud_rate == 0 || baud_rate > 19200 {
+        Duration::from_micros(1750)
+    } else {
+        // a character is 11 bits long including start, parity and stop bits
+        Duration::from_micros(3_500_000 * 11 / baud_rate as u64)
+    }
+}
+
+/// `get_crc` the checksum trailing the frames of a `read` loop, which needs a framer
+fn get_crc(options: &ReadOptions) -> Result<Option<Crc>, Error> {
+    let name = match &options.crc {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    if options.delimiter.is_none() && options.packet_size.is_none() && options.framing.is_none() {
+        return Err(Error::InvalidArgument(
+            "crc needs a delimiter, packet_size or framing to find the frames!".to_string(),
+        ));
+    }
+    get_crc_algorithm(name).map(Some)
+}
+
+fn get_crc_algorithm(name: &str) -> Result<Crc, Error> {
+    Crc::from_name(name).ok_or_else(|| {
+        Error::InvalidArgument(format!(
+            "Invalid crc {:?}, expected \"crc8\", \"crc16-ccitt\", \"crc16-modbus\" or \"crc32\"",
+            name
+        ))
+    })
+}
+
+fn get_read_encoding(value: Option<String>) -> Result<ReadEncoding, Error> {
+    match value {
+        Some(value) => match value.as_str() {
+            "raw" => Ok(ReadEncoding::Raw),
+            "hex" => Ok(ReadEncoding::Hex),
+            "base64" => Ok(ReadEncoding::Base64),
+            _ => Err(Error::InvalidArgument(format!(
+                "Invalid read encoding {:?}, expected \"raw\", \"hex\" or \"base64\"",
+                value
+            ))),
+        },
+        None => Ok(ReadEncoding::Raw),
+    }
+}
+
+/// `apply_config` set every setting in `config` on the open port at `path`
+fn apply_config(
+    serialport: &mut dyn SerialPort,
+    path: &str,
+    config: &SerialportConfig,
+) -> Result<(), Error> {
+    let data_bits = get_data_bits(Some(config.data_bits as usize));
+    let flow_control = get_flow_control(Some(config.flow_control.clone()))?;
+    let parity = get_parity(Some(config.parity.clone()))?;
+    let stop_bits = get_stop_bits(Some(config.stop_bits as usize));
+    serialport
+        .set_baud_rate(config.baud_rate)
+        .and_then(|_| serialport.set_data_bits(data_bits))
+        .and_then(|_| serialport.set_flow_control(flow_control))
+        .and_then(|_| serialport.set_parity(parity))
+        .and_then(|_| serialport.set_stop_bits(stop_bits))
+        .and_then(|_| {
+            // on Windows setting the timeout also resets the inter-character timeout
+            let timeout = Duration::from_millis(config.timeout_ms);
+            if serialport.timeout() == timeout {
+                Ok(())
+            } else {
+                serialport.set_timeout(timeout)
+            }
+        })
+        .map_err(|error| {
+            Error::SerialError(
+                error.kind,
+                format!(
+                    "Error updating settings of serial port {}: {}",
+                    path, error.description
+                ),
+            )
+        })
+}
+
+/// `is_com_name` Whether `path` is a bare Windows COM port name such as `COM10`
+fn is_com_name(path: &str) -> bool {
+    match path.strip_prefix("COM") {
+        Some(number) => !number.is_empty() && number.bytes().all(|byte| byte.is_ascii_digit()),
+        None => false,
+    }
+}
+
+/// `normalize_path` prefix bare COM port names with `\\.\` on Windows, which ports above COM9
+/// cannot be opened without
+fn normalize_path(path: &str) -> String {
+    if cfg!(windows) && is_com_name(path) {
+        format!(r"\\.\{}", path)
+    } else {
+        path.to_string()
+    }
+}
+
+/// `get_port_builder` a builder opening the port at `path` with `config`
+pub(crate) fn get_port_builder(
+    path: &str,
+    config: &SerialportConfig,
+) -> Result<SerialPortBuilder, Error> {
+    Ok(serialport::new(normalize_path(path), config.baud_rate)
+        .data_bits(get_data_bits(Some(config.data_bits as usize)))
+        .flow_control(get_flow_control(Some(config.flow_control.clone()))?)
+        .parity(get_parity(Some(config.parity.clone()))?)
+        .stop_bits(get_stop_bits(Some(config.stop_bits as usize)))
+        .timeout(Duration::from_millis(config.timeout_ms)))
+}
+
+/// `open_serial` open the port configured by `builder` with the inter-character timeout of
+/// `config`, along with its file descriptor, which the `eventfd` read loop can wait on
+fn open_serial(
+    builder: SerialPortBuilder,
+    config: &SerialportConfig,
+) -> serialport::Result<(Box<dyn SerialPort>, Option<i32>)> {
+    let inter_char_timeout = config.inter_char_timeout_ms.map(Duration::from_millis);
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::AsRawFd;
+
+        let serial = builder.open_native()?;
+        if let Some(inter_char_timeout) = inter_char_timeout {
+            crate::inter_char::set(&serial, inter_char_timeout)?;
+        }
+        let raw_fd = serial.as_raw_fd();
+        Ok((Box::new(serial), Some(raw_fd)))
+    }
+    #[cfg(windows)]
+    {
+        let serial = builder.open_native()?;
+        if let Some(inter_char_timeout) = inter_char_timeout {
+            crate::inter_char::set(&serial, inter_char_timeout)?;
+        }
+        Ok((Box::new(serial), None))
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        if inter_char_timeout.is_some() {
+            warn!("Inter-character timeouts are not supported on this platform, ignoring it");
+        }
+        builder.open().map(|serial| (serial, None))
+    }
+}
+
+/// `new_serialport_info` the state of `serial` just opened at `path`
+fn new_serialport_info(
+    serial: Box<dyn SerialPort>,
+    path: &str,
+    config: SerialportConfig,
+    recorder: Option<&Arc<Recorder>>,
+    _raw_fd: Option<i32>,
+) -> SerialportInfo {
+    let serial: Box<dyn SerialPort> = match recorder {
+        Some(recorder) => Box::new(RecordingPort::new(serial, path, recorder.clone())),
+        None => serial,
+    };
+    let peek_buffer = PeekBuffer::default();
+    SerialportInfo {
+        serialport: Box::new(PeekablePort::new(serial, peek_buffer.clone())),
+        peek_buffer,
+        sender: None,
+        read_task: None,
+        read_generation: 0,
+        read_options: None,
+        config,
+        counters: Arc::new(SerialportCounters::default()),
+        pending_events: Arc::new(AtomicUsize::new(0)),
+        opened_at: timestamp_ms(),
+        port_type: find_port_type(path),
+        idle_timeout: None,
+        reconnect: None,
+        access_mode: AccessMode::ReadWrite,
+        hex_dump: Arc::new(AtomicBool::new(false)),
+        initialized: false,
+        #[cfg(all(unix, feature = "eventfd"))]
+        raw_fd: _raw_fd,
+        write_queue: None,
+    }
+}
+
+/// `check_vacant` fail unless a port can be registered at `path`, with `AlreadyOpen` if one is
+/// and with `TooManyPorts` if `max_open_ports` are open
+fn check_vacant(
+    config: &SerialportPluginConfig,
+    serialports: &HashMap<String, SerialportInfo>,
+    path: &str,
+) -> Result<(), Error> {
+    if serialports.contains_key(path) {
+        Err(Error::AlreadyOpen(path.to_string()))
+    } else if matches!(config.max_open_ports, Some(max) if serialports.len() >= max) {
+        Err(Error::TooManyPorts(serialports.len()))
+    } else {
+        Ok(())
+    }
+}
+
+/// `open_connection` register the port `connect` opens at `path`, for connections standing in
+/// for serial ports such as TCP connections and named pipes
+///
+/// Connecting can take a while, so the state is not locked meanwhile and is checked for room
+/// before and after.
+fn open_connection<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &SerialportState,
+    path: String,
+    config: SerialportConfig,
+    connect: impl FnOnce(&SerialportConfig) -> io::Result<Box<dyn SerialPort>>,
+) -> Result<(), Error> {
+    check_vacant(
+        &state.config,
+        &*state.serialports.lock().map_err(|_| Error::LockPoisoned)?,
+        &path,
+    )?;
+    let serial = connect(&config)
+        .map_err(|error| Error::IoError(format!("Error opening {}: {}", path, error)))?;
+    let mut serialports = state.serialports.lock().map_err(|_| Error::LockPoisoned)?;
+    check_vacant(&state.config, &serialports, &path)?;
+    let serialport_info = new_serialport_info(serial, &path, config, state.recorder.as_ref(), None);
+    serialports.insert(path.clone(), serialport_info);
+    drop(serialports);
+    emit_port_event(app, "plugin-serialport-port-opened", &path);
+    Ok(())
+}
+
+/// Receives what a read loop reads from its port.
+pub(crate) trait ReadSink: Send + 'static {
+    /// Handle data read at `timestamp_ms`, returning `false` to stop the loop.
+    fn data(&mut self, data: &[u8], timestamp_ms: u64) -> bool;
+    /// Handle a failed read, timeouts excluded.
+    fn error(&mut self, error: &io::Error);
+    /// Handle a frame read at `timestamp_ms` that is dropped because its checksum does not match.
+    fn crc_error(&mut self, _frame: &[u8], _timestamp_ms: u64) {}
+    /// Handle a message dropped by the framer because it could not be decoded.
+    fn framing_error(&mut self, _message: &str) {}
+    /// Handle the port being closed for exceeding its idle timeout.
+    fn idle(&mut self, _path: &str) {}
+    /// Handle waiting `delay_ms` before reconnect `attempt` of a lost port.
+    fn reconnecting(&mut self, _path: &str, _attempt: u32, _delay_ms: u64) {}
+    /// Handle a lost port being reopened.
+    fn reconnected(&mut self, _path: &str) {}
+    /// Handle nothing being received for `duration_ms`, once until data arrives again.
+    fn silence(&mut self, _duration_ms: u64) {}
+}
+
+/// Emits reads as `plugin-serialport-read-{path}`, failures as `plugin-serialport-error-{path}`,
+/// undecodable messages as `plugin-serialport-framing-error-{path}`, frames with a wrong checksum
+/// as `plugin-serialport-crc-error-{path}` and idle timeouts as
+/// `plugin-serialport-idle-{path}` events to a window, along with the
+/// `plugin-serialport-reconnecting-{path}` and `plugin-serialport-reconnected-{path}` events.
+///
+/// With `"nmea"` framing the sentences are emitted as `NmeaData` and invalid ones as
+/// `plugin-serialport-nmea-error-{path}` instead.
+///
+/// Once `max_pending_events` reads are waiting for `acknowledge_read`, further reads are dropped
+/// and `plugin-serialport-overflow-{path}` is emitted.
+struct EventSink<R: Runtime> {
+    window: Window<R>,
+    path: String,
+    read_event: String,
+    error_event: String,
+    framing_error_event: String,
+    crc_error_event: String,
+    idle_event: String,
+    reconnecting_event: String,
+    reconnected_event: String,
+    overflow_event: String,
+    nmea_error_event: String,
+    silence_event: String,
+    read_encoding: ReadEncoding,
+    /// Whether the read loop splits NMEA sentences.
+    nmea: bool,
+    max_pending_events: Option<usize>,
+    pending_events: Arc<AtomicUsize>,
+    counters: Arc<SerialportCounters>,
+    /// Whether the last read was dropped, so the overflow is only reported once.
+    overflowing: bool,
+}
+
+impl<R: Runtime> ReadSink for EventSink<R> {
+    fn data(&mut self, data: &[u8], timestamp_ms: u64) -> bool {
+        if let Some(max_pending_events) = self.max_pending_events {
+            if self.pending_events.load(Ordering::Relaxed) >= max_pending_events {
+                self.counters.record_dropped(data.len());
+                if !self.overflowing {
+                    self.overflowing = true;
+                    warn!("Serial port {} overflowed, dropping data!", &self.path);
+                    if let Err(error) = self.window.emit(
+                        &self.overflow_event,
+                        OverflowData {
+                            port_name: self.path.clone(),
+                            dropped_bytes: self.counters.dropped_bytes.load(Ordering::Relaxed),
+                        },
+                    ) {
+                        error!("Failed to send overflow: {}", error)
+                    }
+                }
+                return true;
+            }
+            self.pending_events.fetch_add(1, Ordering::Relaxed);
+            self.overflowing = false;
+        }
+        let result = if self.nmea {
+            let sentence = String::from_utf8_lossy(data).into_owned();
+            self.window.emit(
+                &self.read_event,
+                NmeaData {
+                    sentence_type: sentence[1..]
+                        .split(|c| c == ',' || c == '*')
+                        .next()
+                        .unwrap_or_default()
+                        .to_string(),
+                    sentence,
+                    timestamp_ms,
+                },
+            )
+        } else {
+            self.window.emit(
+                &self.read_event,
+                ReadData {
+                    data: encode_read_data(self.read_encoding, data),
+                    size: data.len(),
+                    timestamp_ms,
+                },
+            )
+        };
+        if let Err(error) = result {
+            error!("Failed to send data: {}", error)
+        }
+        true
+    }
+
+    fn error(&mut self, error: &io::Error) {
+        if let Err(error) = self.window.emit(
+            &self.error_event,
+            ErrorData {
+                message: error.to_string(),
+                kind: format!("{:?}", error.kind()),
+            },
+        ) {
+            error!("Failed to send error: {}", error)
+        }
+    }
+
+    fn crc_error(&mut self, frame: &[u8], timestamp_ms: u64) {
+        if let Err(error) = self.window.emit(
+            &self.crc_error_event,
+            ReadData {
+                data: encode_read_data(self.read_encoding, frame),
+                size: frame.len(),
+                timestamp_ms,
+            },
+        ) {
+            error!("Failed to send crc error: {}", error)
+        }
+    }
+
+    fn framing_error(&mut self, message: &str) {
+        let event = if self.nmea {
+            &self.nmea_error_event
+        } else {
+            &self.framing_error_event
+        };
+        if let Err(error) = self.window.emit(
+            event,
+            ErrorData {
+                message: message.to_string(),
+                kind: format!("{:?}", io::ErrorKind::InvalidData),
+            },
+        ) {
+            error!("Failed to send framing error: {}", error)
+        }
+    }
+
+    fn idle(&mut self, path: &str) {
+        if let Err(error) = self.window.emit(
+            &self.idle_event,
+            PortEvent {
+                port_name: path.to_string(),
+            },
+        ) {
+            error!("Failed to send idle timeout: {}", error)
+        }
+    }
+
+    fn reconnecting(&mut self, path: &str, attempt: u32, delay_ms: u64) {
+        if let Err(error) = self.window.emit(
+            &self.reconnecting_event,
+            ReconnectData {
+                port_name: path.to_string(),
+                attempt,
+                delay_ms,
+            },
+        ) {
+            error!("Failed to send reconnect attempt: {}", error)
+        }
+    }
+
+    fn reconnected(&mut self, path: &str) {
+        if let Err(error) = self.window.emit(
+            &self.reconnected_event,
+            PortEvent {
+                port_name: path.to_string(),
+            },
+        ) {
+            error!("Failed to send reconnect: {}", error)
+        }
+    }
+
+    fn silence(&mut self, duration_ms: u64) {
+        if let Err(error) = self
+            .window
+            .emit(&self.silence_event, SilenceData { duration_ms })
+        {
+            error!("Failed to send silence: {}", error)
+        }
+    }
+}
+
+/// Prefix of the paths of Windows named pipes, see `open_pipe`.
+const PIPE_PREFIX: &str = r"\\.\pipe\";
+
+/// Delay of the read loop between two reads while data is arriving.
+const MIN_READ_DELAY: Duration = Duration::from_millis(1);
+
+/// Number of read loops started, making their generations unique.
+static READ_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Longest time `stop_read` waits for a read loop to exit.
+const STOP_READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest delay between two reconnect attempts.
+const MAX_RECONNECT_DELAY_MS: u64 = 30_000;
+
+/// `is_disconnect` Whether a failed read means the device behind the port is gone
+fn is_disconnect(error: &io::Error) -> bool {
+    match error.kind() {
+        io::ErrorKind::BrokenPipe
+        | io::ErrorKind::NotConnected
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::UnexpectedEof => true,
+        // EIO, ENXIO and ENODEV, reported by Linux and macOS for unplugged USB adapters
+        _ => cfg!(unix) && matches!(error.raw_os_error(), Some(5) | Some(6) | Some(19)),
+    }
+}
+
+/// `reconnect_port` reopen a lost port with exponential backoff, returning a new handle for the
+/// read loop or `None` once the retries are exhausted, the port was closed or `stop` signalled
+fn reconnect_port<S: ReadSink>(
+    sink: &mut S,
+    serialports: &Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    path: &str,
+    reconnect: &ReconnectOptions,
+    stop: &crossbeam_channel::Receiver<usize>,
+) -> Option<Box<dyn SerialPort>> {
+    for attempt in 1..=reconnect.max_retries {
+        let delay_ms = reconnect
+            .base_delay_ms
+            .saturating_mul(2u64.saturating_pow(attempt - 1))
+            .min(MAX_RECONNECT_DELAY_MS);
+        warn!(
+            "Reconnecting serial port {} in {}ms, attempt {}",
+            path, delay_ms, attempt
+        );
+        sink.reconnecting(path, attempt, delay_ms);
+        match stop.recv_timeout(Duration::from_millis(delay_ms)) {
+            Err(crossbeam_channel::RecvTimeoutError::Timeout) => {}
+            _ => return None,
+        }
+        let (config, peek_buffer) = match serialports.lock() {
+            Ok(map) => match map.get(path) {
+                Some(serialport_info) => (
+                    serialport_info.config.clone(),
+                    serialport_info.peek_buffer.clone(),
+                ),
+                None => return None,
+            },
+            Err(_) => return None,
+        };
+        let (serial, _raw_fd) =
+            match get_port_builder(path, &config).map(|builder| open_serial(builder, &config)) {
+                Ok(Ok(opened)) => opened,
+                Ok(Err(error)) => {
+                    error!("Failed to reopen serial port {}: {}", path, error);
+                    continue;
+                }
+                Err(_) => return None,
+            };
+        let serial: Box<dyn SerialPort> = match &reconnect.recorder {
+            Some(recorder) => Box::new(RecordingPort::new(serial, path, recorder.clone())),
+            None => serial,
+        };
+        // bytes peeked before the port was lost are stale
+        peek::lock(&peek_buffer).clear();
+        let serial: Box<dyn SerialPort> = Box::new(PeekablePort::new(serial, peek_buffer));
+        let handle = match serial.try_clone() {
+            Ok(handle) => handle,
+            Err(error) => {
+                error!("Failed to reopen serial port {}: {}", path, error);
+                continue;
+            }
+        };
+        match serialports.lock() {
+            Ok(mut map) => match map.get_mut(path) {
+                Some(serialport_info) => {
+                    serialport_info.serialport = serial;
+                    #[cfg(all(unix, feature = "eventfd"))]
+                    {
+                        serialport_info.raw_fd = _raw_fd;
+                    }
+                }
+                None => return None,
+            },
+            Err(_) => return None,
+        }
+        info!("Reconnected serial port {}", path);
+        sink.reconnected(path);
+        return Some(handle);
+    }
+    None
+}
+
+/// `start_read` start a read loop of an open port on the worker pool that passes what it reads
+/// to `sink`, returning `false` if the port is already being read
+pub(crate) fn start_read<S: ReadSink>(
+    serialport_info: &mut SerialportInfo,
+    mut sink: S,
+    pool: &ThreadPool,
+    serialports: Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    path: String,
+    options: ReadOptions,
+) -> Result<bool, Error> {
+    let mut framer = get_framer(&options, serialport_info.config.baud_rate)?;
+    let crc = get_crc(&options)?;
+    let emit_per_packet = options.emit_per_packet == Some(true);
+    if options.batch_ms.is_some() && (!framer.emits_combined() || emit_per_packet) {
+        return Err(Error::InvalidArgument(
+            "batch_ms cannot be combined with packet_size, framing or emit_per_packet!".to_string(),
+        ));
+    }
+    let batch_window = options.batch_ms.map(Duration::from_millis);
+    let silence_timeout = options.silence_timeout_ms.map(Duration::from_millis);
+    if !serialport_info.access_mode.can_read() {
+        return Err(Error::AccessDenied {
+            path,
+            operation: "reading",
+        });
+    }
+    let delimiter = options.delimiter.clone();
+    let max_read_errors = options.max_read_errors.unwrap_or(5);
+    let timeout = options.timeout;
+    let size = options.size;
+    if serialport_info.sender.is_some() {
+        warn!("Serial port {} is already being read!", &path);
+        return Ok(false);
+    }
+    debug!("Starting to read serial port {}!", &path);
+    let mut serial = match serialport_info.serialport.try_clone() {
+        Ok(serial) => serial,
+        Err(error) => {
+            return Err(Error::SerialError(
+                error.kind,
+                format!("Serial port {} read error: {}", &path, error.description),
+            ));
+        }
+    };
+    // checked on every iteration, lock free while empty unlike `mpsc`
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    let counters = serialport_info.counters.clone();
+    let hex_dump = serialport_info.hex_dump.clone();
+    let opened_at = serialport_info.opened_at;
+    let idle_timeout = serialport_info.idle_timeout;
+    let reconnect = serialport_info.reconnect.clone();
+    #[cfg(all(unix, feature = "eventfd"))]
+    let mut readiness = serialport_info
+        .raw_fd
+        .and_then(crate::readiness::Readiness::new);
+    let (task_tx, task_rx) = mpsc::channel();
+    let generation = READ_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    serialport_info.sender = Some(tx);
+    serialport_info.read_task = Some(task_rx);
+    serialport_info.read_generation = generation;
+    serialport_info.read_options = Some(options);
+    pool.execute(move || {
+        let _ = task_tx.send(());
+        // stopping on its own, the loop clears its stop signal so the port can be read again,
+        // unless a newer loop already took its place
+        let release = || {
+            if let Ok(mut map) = serialports.lock() {
+                if let Some(serialport_info) = map.get_mut(&path) {
+                    if serialport_info.read_generation == generation {
+                        serialport_info.sender = None;
+                    }
+                }
+            }
+        };
+        let mut read_errors: u32 = 0;
+        // allocated once, `read` overwrites the front of it on every iteration
+        let mut serial_buf: Vec<u8> = vec![0; size.unwrap_or(1024)];
+        // polls again right away while data arrives, backing off to `timeout` once it stops
+        let max_delay = Duration::from_millis(timeout.unwrap_or(200));
+        let mut delay = MIN_READ_DELAY.min(max_delay);
+        // data held back by `batch_ms`, with the time its first bytes were read
+        let mut batch: Vec<u8> = vec![];
+        let mut batch_read_at = 0;
+        let mut batch_started: Option<Instant> = None;
+        // reported once per silence, the read starting it like a received byte would
+        let mut last_received = Instant::now();
+        let mut silent = false;
+        loop {
+            match rx.try_recv() {
+                Ok(_) => {
+                    if !batch.is_empty() {
+                        sink.data(&batch, batch_read_at);
+                    }
+                    debug!("Done reading serial port {}!", &path);
+                    break;
+                }
+                Err(error) => match error {
+                    TryRecvError::Disconnected => {
+                        debug!("Serial port {} is disconnected!", &path);
+                        break;
+                    }
+                    TryRecvError::Empty => {}
+                },
+            }
+            let frames = match serial.read(serial_buf.as_mut_slice()) {
+                Ok(size) => {
+                    read_errors = 0;
+                    if size > 0 {
+                        delay = MIN_READ_DELAY.min(max_delay);
+                        last_received = Instant::now();
+                        silent = false;
+                    }
+                    counters.record_read(size);
+                    trace!("Serial port {} read data: {}", &path, size);
+                    if size > 0 && hex_dump.load(Ordering::Relaxed) {
+                        print_hex_dump(&path, "read", &serial_buf[..size]);
+                    }
+                    framer.push(&serial_buf[..size])
+                }
+                // a signal arrived before any data, retrying right away loses nothing
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                // frames ended by a pause are complete once nothing else arrived in time, and a
+                // non-blocking port without data is waited for like one that timed out
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+                    ) =>
+                {
+                    delay = (delay * 2).min(max_delay);
+                    framer.poll()
+                }
+                Err(error) => {
+                    delay = (delay * 2).min(max_delay);
+                    read_errors += 1;
+                    counters.record_read_error();
+                    error!("Failed to read serial port {}: {}", &path, error);
+                    sink.error(&error);
+                    if let (Some(reconnect), true) = (&reconnect, is_disconnect(&error)) {
+                        match reconnect_port(&mut sink, &serialports, &path, reconnect, &rx) {
+                            Some(reconnected) => {
+                                serial = reconnected;
+                                #[cfg(all(unix, feature = "eventfd"))]
+                                {
+                                    readiness = serialports.lock().ok().and_then(|map| {
+                                        map.get(&path)
+                                            .and_then(|serialport_info| serialport_info.raw_fd)
+                                            .and_then(crate::readiness::Readiness::new)
+                                    });
+                                }
+                                read_errors = 0;
+                                continue;
+                            }
+                            None => {
+                                warn!("Stopped reading serial port {}, it is lost!", &path);
+                                release();
+                                break;
+                            }
+                        }
+                    }
+                    if read_errors >= max_read_errors {
+                        warn!(
+                            "Stopped reading serial port {} after {} consecutive errors!",
+                            &path, read_errors
+                        );
+                        release();
+                        break;
+                    }
+                    vec![]
+                }
+            };
+            let read_at = timestamp_ms();
+            let frames = match crc {
+                Some(crc) => check_crc(&mut sink, crc, frames, delimiter.as_deref(), read_at),
+                None => frames,
+            };
+            let keep_reading = if let Some(batch_window) = batch_window {
+                if batch_started.is_none() && frames.iter().any(|frame| !frame.is_empty()) {
+                    batch_started = Some(Instant::now());
+                    batch_read_at = read_at;
+                }
+                for frame in &frames {
+                    batch.extend_from_slice(frame);
+                }
+                match batch_started {
+                    Some(started) if started.elapsed() >= batch_window => {
+                        batch_started = None;
+                        sink.data(&std::mem::take(&mut batch), batch_read_at)
+                    }
+                    _ => true,
+                }
+            } else if !framer.emits_combined() || emit_per_packet {
+                frames.iter().all(|frame| sink.data(frame, read_at))
+            } else if !frames.is_empty() {
+                sink.data(&frames.concat(), read_at)
+            } else {
+                true
+            };
+            for message in framer.take_errors() {
+                warn!("Serial port {} framing error: {}", &path, message);
+                sink.framing_error(&message);
+            }
+            if !keep_reading {
+                warn!(
+                    "Stopped reading serial port {}, nothing receives the data!",
+                    &path
+                );
+                release();
+                break;
+            }
+            if let Some(idle_timeout) = idle_timeout {
+                let last_activity = counters
+                    .last_activity
+                    .load(Ordering::Relaxed)
+                    .max(opened_at);
+                if timestamp_ms().saturating_sub(last_activity) >= idle_timeout.as_millis() as u64 {
+                    info!("Closing serial port {} after being idle!", &path);
+                    sink.idle(&path);
+                    // same as `force_close`, the dropped port stays open until this loop exits
+                    if let Ok(mut map) = serialports.lock() {
+                        map.remove(&path);
+                    }
+                    break;
+                }
+            }
+            if let (Some(silence_timeout), false) = (silence_timeout, silent) {
+                let silence = last_received.elapsed();
+                if silence >= silence_timeout {
+                    silent = true;
+                    sink.silence(silence.as_millis() as u64);
+                }
+            }
+            // with `eventfd` the loop wakes up as soon as data arrives, and notices a stop within
+            // `timeout` like it does when sleeping
+            #[cfg(all(unix, feature = "eventfd"))]
+            if let Some(readiness) = &readiness {
+                readiness.wait(max_delay);
+                continue;
+            }
+            thread::sleep(delay);
+        }
+        let _ = task_tx.send(());
+    });
+    Ok(true)
+}
+
+/// `check_crc` strip the trailing `crc` of each frame, passing the frames it does not match to
+/// `sink` as they were received
+///
+/// The checksum of a delimited frame precedes the delimiter, which is kept.
+fn check_crc<S: ReadSink>(
+    sink: &mut S,
+    crc: Crc,
+    frames: Vec<Vec<u8>>,
+    delimiter: Option<&[u8]>,
+    read_at: u64,
+) -> Vec<Vec<u8>> {
+    let delimiter = delimiter.unwrap_or_default();
+    frames
+        .into_iter()
+        .filter_map(
+            |frame| match crc.check(&frame[..frame.len() - delimiter.len()]) {
+                Some(payload) => Some([payload, delimiter].concat()),
+                None => {
+                    warn!("Dropped a frame with a wrong {:?} checksum", crc);
+                    sink.crc_error(&frame, read_at);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+/// `start_event_read` start a read loop emitting events to `window`
+fn start_event_read<R: Runtime>(
+    serialport_info: &mut SerialportInfo,
+    window: Window<R>,
+    pool: &ThreadPool,
+    serialports: Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    path: String,
+    options: ReadOptions,
+) -> Result<(), Error> {
+    let window = match &options.target_window {
+        Some(label) => window
+            .get_window(label)
+            .ok_or_else(|| Error::InvalidArgument(format!("Window {} not found", label)))?,
+        None => window,
+    };
+    // events of an earlier loop are not acknowledged anymore once it stopped
+    serialport_info.pending_events.store(0, Ordering::Relaxed);
+    let sink = EventSink {
+        window,
+        path: path.clone(),
+        read_event: read_event_name(&path, &options),
+        error_event: format!("plugin-serialport-error-{}", &path),
+        framing_error_event: format!("plugin-serialport-framing-error-{}", &path),
+        crc_error_event: format!("plugin-serialport-crc-error-{}", &path),
+        idle_event: format!("plugin-serialport-idle-{}", &path),
+        reconnecting_event: format!("plugin-serialport-reconnecting-{}", &path),
+        reconnected_event: format!("plugin-serialport-reconnected-{}", &path),
+        overflow_event: format!("plugin-serialport-overflow-{}", &path),
+        nmea_error_event: format!("plugin-serialport-nmea-error-{}", &path),
+        silence_event: format!("plugin-serialport-silence-{}", &path),
+        read_encoding: get_read_encoding(options.read_encoding.clone())?,
+        nmea: options.framing.as_deref() == Some("nmea"),
+        max_pending_events: options.max_pending_events,
+        pending_events: serialport_info.pending_events.clone(),
+        counters: serialport_info.counters.clone(),
+        overflowing: false,
+    };
+    start_read(serialport_info, sink, pool, serialports, path, options).map(|_| ())
+}
+
+/// `read_event_name` Get the event a read loop started with `options` emits its data under
+fn read_event_name(path: &str, options: &ReadOptions) -> String {
+    match &options.event_name {
+        Some(event_name) => event_name.clone(),
+        None => format!("plugin-serialport-read-{}", path),
+    }
+}
+
+/// `stop_read` signal a read loop to stop and wait until it has exited
+pub(crate) fn stop_read(
+    path: &str,
+    sender: Option<crossbeam_channel::Sender<usize>>,
+    read_task: Option<Receiver<()>>,
+) -> Result<(), Error> {
+    if let Some(sender) = sender {
+        // the loop has either stopped on its own or has a stop signal pending already
+        let _ = sender.try_send(1);
+    }
+    if let Some(read_task) = read_task {
+        // the loop reports once when it starts and once when it exits
+        let deadline = Instant::now() + STOP_READ_TIMEOUT;
+        let mut started = false;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match read_task.recv_timeout(remaining) {
+                Ok(()) if !started => started = true,
+                Ok(()) => break,
+                // a loop still queued for a worker sees the stop signal as soon as it starts
+                Err(RecvTimeoutError::Timeout) if !started => break,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(Error::IoError(format!(
+                        "Error cancelling read: read loop of serial port {} did not stop",
+                        path
+                    )));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(Error::IoError(format!(
+                        "Error cancelling read: read loop of serial port {} panicked",
+                        path
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `start_write_queue` start the thread writing the data queued for the port at `path`
+///
+/// The thread stops once the returned queue is dropped along with the port.
+fn start_write_queue(
+    serialports: Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    path: String,
+) -> WriteQueue {
+    let writes: Arc<Mutex<QueuedWrites>> = Arc::default();
+    let drained = Arc::new(Condvar::new());
+    let (tx, rx) = mpsc::channel();
+    let queue = writes.clone();
+    let notify = drained.clone();
+    // the queue is written down to the last write even once the port is closed, each write
+    // failing then, so `flush_write_queue` always wakes up
+    thread::spawn(move || {
+        while rx.recv().is_ok() {
+            loop {
+                let (data, delay) = match queue.lock() {
+                    Ok(queue) => match queue.pending.front() {
+                        Some(write) => write.clone(),
+                        None => break,
+                    },
+                    Err(_) => return,
+                };
+                let result = write_paced(&serialports, &path, &data, delay);
+                match queue.lock() {
+                    Ok(mut queue) => {
+                        queue.pending.pop_front();
+                        if let Err(error) = result {
+                            error!("Failed to write queued data to {}: {}", &path, error);
+                            queue.error.get_or_insert(error.to_string());
+                        }
+                        if queue.pending.is_empty() {
+                            notify.notify_all();
+                        }
+                    }
+                    Err(_) => return,
+                }
+            }
+        }
+        debug!("Stopped writing queued data to {}!", &path);
+    });
+    WriteQueue {
+        writes,
+        drained,
+        sender: tx,
+    }
+}
+
+/// `write_paced` write `data` to the port at `path`, one byte at a time `delay` apart when it is
+/// set, locking the port only while writing
+fn write_paced(
+    serialports: &Mutex<HashMap<String, SerialportInfo>>,
+    path: &str,
+    data: &[u8],
+    delay: Option<Duration>,
+) -> Result<(), Error> {
+    let chunk_size = if delay.is_some() {
+        1
+    } else {
+        data.len().max(1)
+    };
+    for (index, chunk) in data.chunks(chunk_size).enumerate() {
+        if let (Some(delay), true) = (delay, index > 0) {
+            thread::sleep(delay);
+        }
+        let mut map = serialports.lock().map_err(|_| Error::LockPoisoned)?;
+        match map.get_mut(path) {
+            Some(serialport_info) => write_all_bytes(serialport_info, path, chunk, None)?,
+            None => return Err(Error::PortNotFound(path.to_string())),
+        }
+    }
+    Ok(())
+}
+
+/// `read_response` read from `serial` until `response_size` bytes or one of `delimiters`
+/// arrived, or everything received before `deadline` when neither is set
+///
+/// A delimited response is read one byte at a time, so nothing following it is consumed.
+fn read_response(
+    serial: &mut dyn SerialPort,
+    counters: &SerialportCounters,
+    path: &str,
+    deadline: Instant,
+    response_size: Option<usize>,
+    delimiters: &[&[u8]],
+) -> Result<Vec<u8>, Error> {
+    let mut response = vec![];
+    let mut buf = vec![0; 1024];
+    while Instant::now() < deadline {
+        let wanted = match response_size {
+            Some(response_size) => response_size - response.len(),
+            None if !delimiters.is_empty() => 1,
+            None => buf.len(),
+        };
+        match serial.read(&mut buf[..wanted.min(1024)]) {
+            Ok(size) => {
+                counters.record_read(size);
+                response.extend_from_slice(&buf[..size]);
+            }
+            Err(error) if error.kind() == io::ErrorKind::TimedOut => {}
+            Err(error) => {
+                return Err(Error::IoError(format!(
+                    "Error reading response of serial port {}: {}",
+                    path, error
+                )));
+            }
+        }
+        if Some(response.len()) == response_size
+            || delimiters
+                .iter()
+                .any(|delimiter| response.ends_with(delimiter))
+        {
+            return Ok(response);
+        }
+    }
+    if response_size.is_none() && delimiters.is_empty() {
+        return Ok(response);
+    }
+    Err(Error::IoError(format!(
+        "Timed out waiting for the response of serial port {}, {} bytes received",
+        path,
+        response.len()
+    )))
+}
+
+/// `port_names` Get the names of the serial ports currently present
+fn port_names() -> HashSet<String> {
+    serialport::available_ports()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|port| port.port_name)
+        .collect()
+}
+
+/// `acknowledge_read` Mark `count` (default 1) read events as handled by the frontend
+///
+/// Only needed for reads started with `max_pending_events`.
+#[command]
+pub fn acknowledge_read<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    count: Option<usize>,
+) -> Result<(), Error> {
+    get_serialport(state, path, |serialport_info| {
+        let count = count.unwrap_or(1);
+        let _ = serialport_info.pending_events.fetch_update(
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |pending| Some(pending.saturating_sub(count)),
+        );
+        Ok(())
+    })
+}
+
+/// `acquire_lease` Reserve the port at `path` for the calling window
+///
+/// Until the lease is released or expires after `ttl_ms` (default 30 seconds), the commands
+/// reading from the port, writing to it or changing its settings and control lines fail with
+/// `PortLeased` for every other window. Opening and closing the port are not affected. Acquiring
+/// it again from the same window extends it and keeps its token. The port does not have to be
+/// open.
+#[command]
+pub fn acquire_lease<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    ttl_ms: Option<u64>,
+) -> Result<LeaseToken, Error> {
+    let window_label = window.label().to_string();
+    let ttl = Duration::from_millis(ttl_ms.unwrap_or(30_000));
+    let mut leases = state.leases.lock().map_err(|_| Error::LockPoisoned)?;
+    let token = match leases.get(&path) {
+        Some(lease) if lease.expires_at > Instant::now() => {
+            if lease.window_label != window_label {
+                return Err(Error::PortLeased(path));
+            }
+            lease.token.clone()
+        }
+        _ => format!(
+            "{:x}-{:x}",
+            timestamp_ms(),
+            state.lease_count.fetch_add(1, Ordering::Relaxed)
+        ),
+    };
+    leases.insert(
+        path.clone(),
+        Lease {
+            token: token.clone(),
+            window_label: window_label.clone(),
+            expires_at: Instant::now() + ttl,
+        },
+    );
+    Ok(LeaseToken {
+        path,
+        token,
+        window_label,
+        expires_at_ms: timestamp_ms() + ttl.as_millis() as u64,
+    })
+}
+
+/// `check_lease` fail with `PortLeased` if a window other than `window` holds a lease on `path`
+fn check_lease<R: Runtime>(
+    state: &SerialportState,
+    path: &str,
+    window: &Window<R>,
+) -> Result<(), Error> {
+    let mut leases = state.leases.lock().map_err(|_| Error::LockPoisoned)?;
+    match leases.get(path) {
+        Some(lease) if lease.expires_at <= Instant::now() => {
+            leases.remove(path);
+            Ok(())
+        }
+        Some(lease) if lease.window_label != window.label() => {
+            Err(Error::PortLeased(path.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `at_command` Send the AT command `cmd` followed by `"\r\n"` and wait up to `timeout_ms` for
+/// the final result code
+///
+/// The response ends with a line reading `OK` or `ERROR`, which is included. The echo of the
+/// command is removed if the device sends one, and surrounding line breaks are trimmed. A running
+/// read loop is paused meanwhile like for `transaction`, and resumed even if the command fails.
+/// The command waits for the response off the async runtime.
+#[command]
+pub async fn at_command<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    cmd: String,
+    timeout_ms: u64,
+) -> Result<String, Error> {
+    check_lease(&state, &path, &window)?;
+    let response = exchange(
+        window,
+        state,
+        path,
+        format!("{}\r\n", cmd).into_bytes(),
+        Duration::from_millis(timeout_ms),
+        None,
+        vec![b"OK\r\n".to_vec(), b"ERROR\r\n".to_vec()],
+    )
+    .await?;
+    let response = String::from_utf8_lossy(&response);
+    let response = response.trim_start_matches(['\r', '\n']);
+    let response = response.strip_prefix(cmd.as_str()).unwrap_or(response);
+    Ok(response.trim_matches(['\r', '\n']).to_string())
+}
+
+/// `at_init` Check that the AT device at `path` responds by sending `AT`, waiting up to
+/// `timeout_ms` (default 1000) for `OK`
+#[command]
+pub async fn at_init<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    timeout_ms: Option<u64>,
+) -> Result<(), Error> {
+    let response = at_command(
+        app,
+        window,
+        state,
+        path.clone(),
+        "AT".to_string(),
+        timeout_ms.unwrap_or(1000),
+    )
+    .await?;
+    if response.ends_with("OK") {
+        Ok(())
+    } else {
+        Err(Error::IoError(format!(
+            "AT device at {} answered {:?} instead of OK",
+            path, response
+        )))
+    }
+}
+
+/// `available_ports` Get the list of serial ports
+#[command]
+pub fn available_ports() -> Vec<String> {
+    let mut list = match serialport::available_ports() {
+        Ok(list) => list,
+        Err(_) => vec![],
+    };
+    list.sort_by(|a, b| a.port_name.cmp(&b.port_name));
+
+    let mut name_list: Vec<String> = vec![];
+    for i in &list {
+        name_list.push(normalize_path(&i.port_name));
+    }
+
+    debug!("Serial ports: {:?}", &name_list);
+
+    name_list
+}
+
+/// `available_ports_info` Get the list of serial ports with their type and USB metadata
+///
+/// On Linux the metadata is completed from sysfs, which also lists USB ports the `serialport`
+/// crate could not enumerate. On Windows the friendly names are added from the registry.
+#[command]
+pub fn available_ports_info() -> Vec<SerialPortDetail> {
+    let list = serialport::available_ports().unwrap_or_default();
+    let mut details: Vec<SerialPortDetail> = list
+        .into_iter()
+        .map(|port| {
+            let mut detail = SerialPortDetail::from(port);
+            detail.port_name = normalize_path(&detail.port_name);
+            detail
+        })
+        .collect();
+    #[cfg(target_os = "linux")]
+    crate::sysfs::merge_usb_ports(
+        &mut details,
+        crate::sysfs::usb_ports(std::path::Path::new("/sys/class/tty")),
+    );
+    #[cfg(windows)]
+    {
+        let friendly_names = crate::registry::friendly_names();
+        for detail in &mut details {
+            let port_name = detail.port_name.trim_start_matches(r"\\.\");
+            detail.friendly_name = friendly_names.get(&port_name.to_uppercase()).cloned();
+        }
+    }
+    details.sort_by(|a, b| a.port_name.cmp(&b.port_name));
+    details
+}
+
+/// `available_ports_filtered` Get the list of serial ports of one type
+///
+/// `port_type` is one of `"USB"`, `"Bluetooth"`, `"PCI"` or `"Unknown"`, `None` lists every port.
+#[command]
+pub fn available_ports_filtered(port_type: Option<String>) -> Result<Vec<String>, Error> {
+    if let Some(port_type) = &port_type {
+        if !["USB", "Bluetooth", "PCI", "Unknown"].contains(&port_type.as_str()) {
+            return Err(Error::InvalidArgument(format!(
+                "Unknown port type {}, expected USB, Bluetooth, PCI or Unknown",
+                port_type
+            )));
+        }
+    }
+    Ok(available_ports_info()
+        .into_iter()
+        .filter(|port| match &port_type {
+            Some(port_type) => port.port_type == *port_type,
+            None => true,
+        })
+        .map(|port| port.port_name)
+        .collect())
+}
+
+/// `find_port_type` the type of `path` in `available_ports_info`, `"Unknown"` if it is not listed
+fn find_port_type(path: &str) -> String {
+    let path = normalize_path(path);
+    available_ports_info()
+        .into_iter()
+        .find(|port| port.port_name == path)
+        .map(|port| port.port_type)
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+/// `get_port_type` Get the type of a serial port, one of `"USB"`, `"Bluetooth"`, `"PCI"` or
+/// `"Unknown"`
+///
+/// Open ports report the type found when they were opened, other paths are looked up in the
+/// list of ports and are `"Unknown"` if they are not listed.
+#[command]
+pub fn get_port_type<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<String, Error> {
+    match state.serialports.lock() {
+        Ok(serialports) => Ok(match serialports.get(&path) {
+            Some(serialport_info) => serialport_info.port_type.clone(),
+            None => find_port_type(&path),
+        }),
+        Err(_) => Err(Error::LockPoisoned),
+    }
+}
+
+/// `batch_open` Open several serial ports, returning the result of each
+///
+/// With `fail_fast` set to `true` the first failure closes the ports opened so far and is
+/// returned as the error, so either all ports are open or none.
+#[command]
+pub async fn batch_open<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    window: Window<R>,
+    ports: Vec<OpenParams>,
+    fail_fast: Option<bool>,
+) -> Result<Vec<Result<(), Error>>, Error> {
+    let mut results = Vec::with_capacity(ports.len());
+    let mut opened: Vec<String> = Vec::new();
+    for params in ports {
+        let path = params.path.clone();
+        let result = open(
+            app.clone(),
+            state.clone(),
+            window.clone(),
+            params.path,
+            params.baud_rate,
+            params.data_bits,
+            params.flow_control,
+            params.parity,
+            params.stop_bits,
+            params.timeout,
+            params.idle_timeout_ms,
+            params.access_mode,
+            params.validate_baud_rate,
+            params.inter_char_timeout_ms,
+            params.init_sequence,
+            params.init_delay_ms,
+        )
+        .await;
+        match result {
+            Ok(()) => opened.push(path),
+            Err(error) if fail_fast == Some(true) => {
+                for path in opened {
+                    force_close(app.clone(), window.clone(), state.clone(), path)?;
+                }
+                return Err(error);
+            }
+            Err(_) => {}
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// `bytes_to_read` Get the number of bytes waiting in the OS receive buffer
+///
+/// The count comes from the operating system driver and is not supported by every platform
+/// or adapter; an error is returned when it cannot be queried.
+#[command]
+pub fn bytes_to_read<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<u32, Error> {
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .bytes_to_read()
+    {
+        Ok(size) => Ok(size),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error getting bytes to read of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })
+}
+
+/// `bytes_to_write` Get the number of bytes waiting in the OS transmit buffer
+///
+/// The count comes from the operating system driver and is not supported by every platform
+/// or adapter; an error is returned when it cannot be queried.
+#[command]
+pub fn bytes_to_write<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<u32, Error> {
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .bytes_to_write()
+    {
+        Ok(size) => Ok(size),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error getting bytes to write of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })
+}
+
+/// `cancel_read` Cancel serial data reading
+///
+/// Returns once the read loop has exited, so a following `read` cannot race it.
+#[command]
+pub async fn cancel_read<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    // take the task out of the state so it is waited on without holding the lock
+    let (sender, read_task, options) = get_serialport(state, path.clone(), |serialport_info| {
+        Ok((
+            serialport_info.sender.take(),
+            serialport_info.read_task.take(),
+            serialport_info.read_options.take(),
+        ))
+    })?;
+    debug!("Cancelling {} serial read", &path);
+    let active = sender.is_some();
+    stop_read(&path, sender, read_task)?;
+    if let (true, Some(options)) = (active, options) {
+        let event = format!("{}-cancelled", read_event_name(&path, &options));
+        let window = match &options.target_window {
+            Some(label) => window.get_window(label).unwrap_or(window),
+            None => window,
+        };
+        if let Err(error) = window.emit(&event, PortEvent { port_name: path }) {
+            error!("Failed to emit {}: {}", event, error);
+        }
+    }
+    Ok(())
+}
+
+/// `clear_input_buffer` Discard bytes received but not yet read
+#[command]
+pub fn clear_input_buffer<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .clear(ClearBuffer::Input)
+    {
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error clearing input buffer of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })
+}
+
+/// `emit_port_event` tell every window, and in tests the `port_event_hook` if any, that the port
+/// at `path` was opened or closed
+fn emit_port_event<R: Runtime>(app: &AppHandle<R>, event: &str, path: &str) {
+    #[cfg(test)]
+    if let Some(state) = app.try_state::<SerialportState>() {
+        if let Some(hook) = &state.port_event_hook {
+            hook(event, path);
+        }
+    }
+    let payload = PortEvent {
+        port_name: path.to_string(),
+    };
+    if let Err(error) = app.emit_all(event, payload) {
+        error!("Failed to emit {}: {}", event, error);
+    }
+}
+
+/// `close` Close serial port
+///
+/// Emits `plugin-serialport-port-closed` to every window.
+#[command]
+pub fn close<R: Runtime>(
+    app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    match state.serialports.lock() {
+        Ok(mut serialports) => {
+            if serialports.remove(&path).is_some() {
+                drop(serialports);
+                emit_port_event(&app, "plugin-serialport-port-closed", &path);
+                Ok(())
+            } else {
+                Err(Error::PortNotFound(path))
+            }
+        }
+        Err(_) => Err(Error::LockPoisoned),
+    }
+}
+
+/// `close_all` Close all serial ports
+///
+/// A window restricted by `window_port_acl` only closes the ports it may use. Emits
+/// `plugin-serialport-port-closed` to every window for each port.
+#[command]
+pub fn close_all<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+) -> Result<(), Error> {
+    match state.serialports.lock() {
+        Ok(mut map) => {
+            let paths = window_ports(&state.config, &window, map.keys());
+            let mut errors: Vec<String> = vec![];
+            for path in &paths {
+                if let Some(mut serialport_info) = map.remove(path) {
+                    if let Some(sender) = serialport_info.sender.take() {
+                        if let Err(error @ TrySendError::Disconnected(_)) = sender.try_send(1) {
+                            errors.push(format!("{}: {}", path, error));
+                        }
+                    }
+                }
+            }
+            drop(map);
+            for path in paths {
+                emit_port_event(&app, "plugin-serialport-port-closed", &path);
+            }
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                error!("Error closing serial ports: {}", errors.join(", "));
+                Err(Error::IoError(format!(
+                    "Error closing serial ports: {}",
+                    errors.join(", ")
+                )))
+            }
+        }
+        Err(_) => Err(Error::LockPoisoned),
+    }
+}
+
+/// `close_all_on_exit` close every port when the app exits, waiting for their read loops to stop
+///
+/// Dropping the ports with the process can leave them unusable until replugged on some
+/// platforms, Windows in particular.
+pub(crate) fn close_all_on_exit(state: &SerialportState) {
+    // taken out of the state first, the read loops lock it while stopping
+    let serialports: Vec<(String, SerialportInfo)> = match state.serialports.lock() {
+        Ok(mut map) => map.drain().collect(),
+        Err(error) => error.into_inner().drain().collect(),
+    };
+    for (path, mut serialport_info) in serialports {
+        let sender = serialport_info.sender.take();
+        let read_task = serialport_info.read_task.take();
+        if let Err(error) = stop_read(&path, sender, read_task) {
+            error!("Failed to stop reading serial port {}: {}", &path, error);
+        }
+        drop(serialport_info);
+        info!("Closed serial port {} on exit", &path);
+    }
+}
+
+/// `close_inactive_ports` Close every port without reads or writes for `inactive_for_ms`
+///
+/// Ports that were never used count as active when they were opened. Closes them like
+/// `force_close` and returns their paths. A window restricted by `window_port_acl` only closes
+/// the ports it may use.
+#[command]
+pub fn close_inactive_ports<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    inactive_for_ms: u64,
+) -> Result<Vec<String>, Error> {
+    let now = timestamp_ms();
+    let mut map = state.serialports.lock().map_err(|_| Error::LockPoisoned)?;
+    let mut paths: Vec<String> = map
+        .iter()
+        .filter(|(path, _)| check_window_access(&state.config, window.label(), path).is_ok())
+        .filter(|(_, serialport_info)| {
+            let last_activity = serialport_info
+                .counters
+                .last_activity
+                .load(Ordering::Relaxed)
+                .max(serialport_info.opened_at);
+            now.saturating_sub(last_activity) >= inactive_for_ms
+        })
+        .map(|(path, _)| path.clone())
+        .collect();
+    paths.sort();
+    for path in &paths {
+        // dropping the port stops its read thread
+        map.remove(path);
+    }
+    drop(map);
+    for path in &paths {
+        info!("Closed serial port {} after being inactive!", path);
+        emit_port_event(&app, "plugin-serialport-port-closed", path);
+    }
+    Ok(paths)
+}
+
+/// `create_virtual_port_pair` Create two connected virtual serial ports for testing
+///
+/// Returns the paths of both ports, which `open` like any other port so that both ends can be
+/// driven from the webview. Needs the `pty` feature and is only supported on Linux and macOS.
+#[command]
+pub fn create_virtual_port_pair<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+) -> Result<(String, String), Error> {
+    #[cfg(feature = "pty")]
+    {
+        let (path_a, path_b) = crate::pty::create_pty_pair()?;
+        info!("Created virtual serial ports {} and {}!", path_a, path_b);
+        Ok((path_a, path_b))
+    }
+    #[cfg(not(feature = "pty"))]
+    Err(Error::Unsupported(
+        "Virtual serial port pairs need the pty feature".to_string(),
+    ))
+}
+
+/// `flush` Wait until all buffered output has been transmitted
+#[command]
+pub fn flush<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        flush_port(serialport_info, &path)
+    })
+}
+
+/// `flush_write_queue` Wait until the data queued by `write_queued` has been written and
+/// transmitted
+///
+/// Fails with the first error the queue ran into since the last call, if any.
+#[command]
+pub async fn flush_write_queue<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    let queue = get_serialport(state.clone(), path.clone(), |serialport_info| {
+        Ok(serialport_info
+            .write_queue
+            .as_ref()
+            .map(|queue| (queue.writes.clone(), queue.drained.clone())))
+    })?;
+    if let Some((writes, drained)) = queue {
+        let error = tauri::async_runtime::spawn_blocking(move || {
+            let mut writes = writes.lock().map_err(|_| Error::LockPoisoned)?;
+            while !writes.pending.is_empty() {
+                writes = drained.wait(writes).map_err(|_| Error::LockPoisoned)?;
+            }
+            Ok::<_, Error>(writes.error.take())
+        })
+        .await
+        .map_err(|error| Error::IoError(error.to_string()))??;
+        if let Some(error) = error {
+            return Err(Error::IoError(error));
+        }
+    }
+    get_serialport(state, path.clone(), |serialport_info| {
+        flush_port(serialport_info, &path)
+    })
+}
+
+/// `force_close` Force close serial port
+///
+/// Emits `plugin-serialport-port-closed` to every window if the port was open.
+#[command]
+pub fn force_close<R: Runtime>(
+    app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    match state.serialports.lock() {
+        Ok(mut map) => {
+            // dropping the port stops its read thread
+            if map.remove(&path).is_some() {
+                drop(map);
+                emit_port_event(&app, "plugin-serialport-port-closed", &path);
+            }
+            Ok(())
+        }
+        Err(_) => Err(Error::LockPoisoned),
+    }
+}
+
+/// `get_port_settings` Get the settings an open serial port was opened with
+#[command]
+pub fn get_port_settings<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<SerialportConfig, Error> {
+    get_serialport(state, path, |serialport_info| {
+        Ok(serialport_info.config.clone())
+    })
+}
+
+/// `get_statistics` Get the read and write statistics of an open serial port
+#[command]
+pub fn get_statistics<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<SerialportStats, Error> {
+    get_serialport(state, path, |serialport_info| {
+        Ok(SerialportStats::new(
+            &serialport_info.counters,
+            serialport_info.opened_at,
+        ))
+    })
+}
+
+/// `is_open` Check whether a serial port is currently open
+#[command]
+pub fn is_open<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> bool {
+    match state.serialports.lock() {
+        Ok(map) => map.contains_key(&path),
+        Err(_) => false,
+    }
+}
+
+/// `list_open_ports` Get the list of currently open serial ports
+///
+/// A window restricted by `window_port_acl` only sees the ports it may use.
+#[command]
+pub fn list_open_ports<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+) -> Vec<String> {
+    match state.serialports.lock() {
+        Ok(map) => {
+            let mut list = window_ports(&state.config, &window, map.keys());
+            list.sort();
+            list
+        }
+        Err(_) => vec![],
+    }
+}
+
+/// `open` Open serial port
+///
+/// Any `baud_rate` is passed on to the driver, so non-standard rates such as 250000 for DMX512
+/// work where the platform supports them: Linux, macOS and most Windows drivers. With
+/// `validate_baud_rate` set to `true` only the standard rates are accepted.
+///
+/// With `inter_char_timeout_ms` a read returns once no byte arrived for that long after the
+/// first one, collecting bursts of variable length in one read. It is set as `VTIME` in tenths of
+/// a second on Linux and macOS, and as `ReadIntervalTimeout` on Windows, where changing the
+/// `timeout` with `update_settings` drops it again. It is ignored on other platforms.
+///
+/// `access_mode` is one of `"read_only"`, `"write_only"` or `"read_write"` (default). Reading a
+/// write only port or writing to a read only one fails with `AccessDenied`.
+///
+/// `init_sequence` is written as soon as the port is open, e.g. to set up a modem, followed by a
+/// pause of `init_delay_ms` for the device to process it. If it cannot be written the port is
+/// closed again and the error returned. `init_delay_ms` is ignored without an `init_sequence`.
+/// Opening and initializing the port run off the async runtime, and the port is only registered,
+/// and usable by other commands, once it is initialized.
+///
+/// Without `baud_rate` the `defaultBaudRate` of the plugin configuration is used, or 9600. Paths
+/// missing from its `allowedPorts`, or not granted to the window by its `windowPortAcl`, fail
+/// with `PermissionDenied`, and opening more than `maxOpenPorts` ports fails with
+/// `TooManyPorts`.
+///
+/// Emits `plugin-serialport-port-opened` to every window once the port is open.
+#[command]
+pub async fn open<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    window: Window<R>,
+    path: String,
+    baud_rate: Option<u32>,
+    data_bits: Option<usize>,
+    flow_control: Option<String>,
+    parity: Option<String>,
+    stop_bits: Option<usize>,
+    timeout: Option<u64>,
+    idle_timeout_ms: Option<u64>,
+    access_mode: Option<String>,
+    validate_baud_rate: Option<bool>,
+    inter_char_timeout_ms: Option<u64>,
+    init_sequence: Option<Vec<u8>>,
+    init_delay_ms: Option<u64>,
+) -> Result<(), Error> {
+    check_allowed_port(&state.config, &path)?;
+    check_window_access(&state.config, window.label(), &path)?;
+    let baud_rate = baud_rate.or(state.config.default_baud_rate).unwrap_or(9600);
+    if validate_baud_rate == Some(true) {
+        check_baud_rate(baud_rate)?;
+    }
+    let access_mode = get_access_mode(access_mode)?;
+    let mut config = SerialportConfig::new(
+        baud_rate,
+        get_data_bits(data_bits),
+        get_flow_control(flow_control)?,
+        get_parity(parity)?,
+        get_stop_bits(stop_bits),
+        Duration::from_millis(timeout.unwrap_or(200)),
+    );
+    config.inter_char_timeout_ms = inter_char_timeout_ms;
+    if normalize_path(&path) != path {
+        info!(
+            "Serial port {} is a bare COM port name, opening {} instead",
+            &path,
+            normalize_path(&path)
+        );
+    }
+    check_vacant(
+        &state.config,
+        &*state.serialports.lock().map_err(|_| Error::LockPoisoned)?,
+        &path,
+    )?;
+    let recorder = state.recorder.clone();
+    let port_path = path.clone();
+    let serialport_info = tauri::async_runtime::spawn_blocking(move || {
+        let path = port_path;
+        let (serial, raw_fd) =
+            open_serial(get_port_builder(&path, &config)?, &config).map_err(|error| {
+                Error::SerialError(
+                    error.kind,
+                    format!("Error opening {}: {}", path, error.description),
+                )
+            })?;
+        let mut serialport_info =
+            new_serialport_info(serial, &path, config, recorder.as_ref(), raw_fd);
+        serialport_info.idle_timeout = idle_timeout_ms.map(Duration::from_millis);
+        serialport_info.access_mode = access_mode;
+        if let Some(init_sequence) = init_sequence {
+            initialize_port(&mut serialport_info, &path, &init_sequence, init_delay_ms)?;
+        }
+        Ok::<_, Error>(serialport_info)
+    })
+    .await
+    .map_err(|error| Error::IoError(error.to_string()))??;
+    let mut serialports = state.serialports.lock().map_err(|_| Error::LockPoisoned)?;
+    // the port is closed again when it is dropped here
+    check_vacant(&state.config, &serialports, &path)?;
+    serialports.insert(path.clone(), serialport_info);
+    drop(serialports);
+    emit_port_event(&app, "plugin-serialport-port-opened", &path);
+    Ok(())
+}
+
+/// `initialize_port` write the `init_sequence` of `open` to the port just opened at `path` and
+/// wait `init_delay_ms` for the device to process it
+///
+/// Blocks for the delay, and runs before the port is registered so nothing else uses it meanwhile.
+pub(crate) fn initialize_port(
+    serialport_info: &mut SerialportInfo,
+    path: &str,
+    init_sequence: &[u8],
+    init_delay_ms: Option<u64>,
+) -> Result<(), Error> {
+    if let Err(error) = write_all_bytes(serialport_info, path, init_sequence, None) {
+        error!("Failed to initialize serial port {}: {}", path, error);
+        return Err(error);
+    }
+    if let Some(init_delay_ms) = init_delay_ms {
+        thread::sleep(Duration::from_millis(init_delay_ms));
+    }
+    serialport_info.initialized = true;
+    debug!("Initialized serial port {}!", path);
+    Ok(())
+}
+
+/// `open_bluetooth` Open an RFCOMM channel of a Bluetooth serial (SPP) device
+///
+/// Connects to the RFCOMM socket directly instead of the virtual COM port, so the device does
+/// not need to be paired first. The port is registered as `bluetooth://{address}/{channel}`
+/// and, like `open_tcp`, passes bytes through unchanged. Linux and Windows only, fails with
+/// `Unsupported` on other platforms.
+#[command]
+pub fn open_bluetooth<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    window: Window<R>,
+    address: String,
+    channel: u8,
+    baud_rate: Option<u32>,
+    timeout: Option<u64>,
+) -> Result<(), Error> {
+    let address = parse_bluetooth_address(&address)?;
+    if !(1..=30).contains(&channel) {
+        return Err(Error::InvalidArgument(format!(
+            "Invalid RFCOMM channel {}, expected 1 to 30",
+            channel
+        )));
+    }
+    let path = format!(
+        "bluetooth://{}/{}",
+        address
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<_>>()
+            .join(":"),
+        channel
+    );
+    check_allowed_port(&state.config, &path)?;
+    check_window_access(&state.config, window.label(), &path)?;
+    let config = SerialportConfig::new(
+        baud_rate.or(state.config.default_baud_rate).unwrap_or(9600),
+        DataBits::Eight,
+        FlowControl::None,
+        Parity::None,
+        StopBits::One,
+        Duration::from_millis(timeout.unwrap_or(200)),
+    );
+    #[cfg(any(target_os = "linux", windows))]
+    {
+        open_connection(&app, &state, path.clone(), config, |config| {
+            let stream = crate::bluetooth::connect(address, channel)?;
+            let serial = TcpSerialPort::from_stream(
+                path,
+                stream,
+                config.baud_rate,
+                Duration::from_millis(config.timeout_ms),
+            )?;
+            Ok(Box::new(serial))
+        })
+    }
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        let _ = (app, config);
+        Err(Error::Unsupported(
+            "Bluetooth RFCOMM is only supported on Linux and Windows".to_string(),
+        ))
+    }
+}
+
+/// `open_pipe` Open a Windows named pipe in place of a serial port, e.g. QEMU's `-serial pipe:`
+///
+/// `name` is the name of the pipe or its full path `\\.\pipe\{name}`, under which the port is
+/// registered and used with the same commands as any other port. Like `open_tcp` the line
+/// settings are only reported back, there are no control signals and `reopen` is not supported.
+/// Fails on other platforms.
+#[command]
+pub fn open_pipe<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    window: Window<R>,
+    name: String,
+    baud_rate: Option<u32>,
+    timeout: Option<u64>,
+) -> Result<(), Error> {
+    let path = if name.starts_with(PIPE_PREFIX) {
+        name
+    } else {
+        format!("{}{}", PIPE_PREFIX, name)
+    };
+    check_allowed_port(&state.config, &path)?;
+    check_window_access(&state.config, window.label(), &path)?;
+    let config = SerialportConfig::new(
+        baud_rate.or(state.config.default_baud_rate).unwrap_or(9600),
+        DataBits::Eight,
+        FlowControl::None,
+        Parity::None,
+        StopBits::One,
+        Duration::from_millis(timeout.unwrap_or(200)),
+    );
+    open_connection(&app, &state, path.clone(), config, |config| {
+        #[cfg(windows)]
+        {
+            let serial = crate::pipe::PipeSerialPort::open(
+                &path,
+                config.baud_rate,
+                Duration::from_millis(config.timeout_ms),
+            )?;
+            Ok(Box::new(serial) as Box<dyn SerialPort>)
+        }
+        #[cfg(not(windows))]
+        {
+            let _ = config;
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "named pipes are only supported on Windows",
+            ))
+        }
+    })
+}
+
+/// `open_tcp` Open a serial port reached over a raw TCP connection, e.g. a serial device server
+///
+/// The port is registered as `tcp://{host}:{port}` and used with the same commands as any other
+/// port. The bytes are passed through unchanged, so the device server has to be configured with
+/// the line settings; `baud_rate` (default 9600) is only reported back. Control signals read as
+/// `false` and breaks are not supported. `reopen` and reconnecting are not supported either.
+#[command]
+pub fn open_tcp<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    window: Window<R>,
+    host: String,
+    port: u16,
+    baud_rate: Option<u32>,
+    timeout: Option<u64>,
+) -> Result<(), Error> {
+    let path = format!("tcp://{}:{}", host, port);
+    check_allowed_port(&state.config, &path)?;
+    check_window_access(&state.config, window.label(), &path)?;
+    let config = SerialportConfig::new(
+        baud_rate.or(state.config.default_baud_rate).unwrap_or(9600),
+        DataBits::Eight,
+        FlowControl::None,
+        Parity::None,
+        StopBits::One,
+        Duration::from_millis(timeout.unwrap_or(200)),
+    );
+    open_connection(&app, &state, path, config, |config| {
+        let serial = TcpSerialPort::connect(
+            &host,
+            port,
+            config.baud_rate,
+            Duration::from_millis(config.timeout_ms),
+        )?;
+        Ok(Box::new(serial))
+    })
+}
+
+/// `open_with_defaults` Open serial port at `baud_rate` with 8 data bits, no parity, one stop
+/// bit, no flow control and a 200 ms timeout
+#[command]
+pub async fn open_with_defaults<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    window: Window<R>,
+    path: String,
+    baud_rate: u32,
+) -> Result<(), Error> {
+    open(
+        app,
+        state,
+        window,
+        path,
+        Some(baud_rate),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await
+}
+
+/// `open_with_reconnect` Open a serial port that is reopened when it is lost while being read
+///
+/// Up to `max_retries` attempts are made, the first after `base_delay_ms` and each following one
+/// after twice the previous delay, at most 30 seconds.
+#[command]
+pub async fn open_with_reconnect<R: Runtime>(
+    app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    window: Window<R>,
+    path: String,
+    baud_rate: Option<u32>,
+    data_bits: Option<usize>,
+    flow_control: Option<String>,
+    parity: Option<String>,
+    stop_bits: Option<usize>,
+    timeout: Option<u64>,
+    idle_timeout_ms: Option<u64>,
+    access_mode: Option<String>,
+    validate_baud_rate: Option<bool>,
+    inter_char_timeout_ms: Option<u64>,
+    max_retries: u32,
+    base_delay_ms: u64,
+) -> Result<(), Error> {
+    let recorder = state.recorder.clone();
+    open(
+        app,
+        state.clone(),
+        window,
+        path.clone(),
+        baud_rate,
+        data_bits,
+        flow_control,
+        parity,
+        stop_bits,
+        timeout,
+        idle_timeout_ms,
+        access_mode,
+        validate_baud_rate,
+        inter_char_timeout_ms,
+        None,
+        None,
+    )
+    .await?;
+    get_serialport(state, path, |serialport_info| {
+        serialport_info.reconnect = Some(ReconnectOptions {
+            max_retries,
+            base_delay_ms,
+            recorder,
+        });
+        Ok(())
+    })
+}
+
+/// `peek` Get up to `count` of the bytes waiting to be read without consuming them
+///
+/// Serial devices cannot be peeked at by the OS, `MSG_PEEK` only works on sockets, so the bytes
+/// are read from the port and kept in a buffer that every later read of the port returns first.
+/// Only bytes already received are returned, fewer than `count` if fewer arrived. Fails with
+/// `InvalidArgument` while `read` runs, since its loop consumes the bytes as they arrive.
+#[command]
+pub fn peek<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    count: usize,
+) -> Result<Vec<u8>, Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        if !serialport_info.access_mode.can_read() {
+            return Err(Error::AccessDenied {
+                path,
+                operation: "reading",
+            });
+        }
+        if serialport_info.sender.is_some() {
+            return Err(Error::InvalidArgument(format!(
+                "Serial port {} is being read, cannot peek at it",
+                path
+            )));
+        }
+        // taken out while reading, so the port returns what follows the bytes peeked before
+        let mut peeked = std::mem::take(&mut *peek::lock(&serialport_info.peek_buffer));
+        let mut result = Ok(());
+        while peeked.len() < count {
+            let available = match serialport_info.serialport.bytes_to_read() {
+                Ok(0) => break,
+                Ok(available) => available as usize,
+                Err(error) => {
+                    result = Err(Error::SerialError(
+                        error.kind,
+                        format!(
+                            "Error peeking at serial port {}: {}",
+                            &path, error.description
+                        ),
+                    ));
+                    break;
+                }
+            };
+            let mut buf = vec![0; (count - peeked.len()).min(available)];
+            match serialport_info.serialport.read(&mut buf) {
+                Ok(0) => break,
+                Ok(size) => peeked.extend_from_slice(&buf[..size]),
+                Err(error) if error.kind() == io::ErrorKind::TimedOut => break,
+                Err(error) => {
+                    result = Err(Error::IoError(format!(
+                        "Error peeking at serial port {}: {}",
+                        &path, error
+                    )));
+                    break;
+                }
+            }
+        }
+        let bytes = peeked[..count.min(peeked.len())].to_vec();
+        let mut peek_buffer = peek::lock(&serialport_info.peek_buffer);
+        peeked.append(&mut peek_buffer);
+        *peek_buffer = peeked;
+        result.map(|_| bytes)
+    })
+}
+
+/// `port_info` Get the settings, statistics and control signal levels of an open serial port in
+/// one call
+#[command]
+pub fn port_info<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<PortInfo, Error> {
+    get_serialport(state, path.clone(), |serialport_info| {
+        let serialport = serialport_info.serialport.as_mut();
+        let signals = serialport
+            .read_clear_to_send()
+            .and_then(|clear_to_send| {
+                Ok(SignalState {
+                    clear_to_send,
+                    data_set_ready: serialport.read_data_set_ready()?,
+                    ring_indicator: serialport.read_ring_indicator()?,
+                    carrier_detect: serialport.read_carrier_detect()?,
+                })
+            })
+            .map_err(|error| {
+                Error::SerialError(
+                    error.kind,
+                    format!(
+                        "Error reading control signals of serial port {}: {}",
+                        &path, error.description
+                    ),
+                )
+            })?;
+        Ok(PortInfo {
+            config: serialport_info.config.clone(),
+            stats: SerialportStats::new(&serialport_info.counters, serialport_info.opened_at),
+            signals,
+            is_reading: serialport_info.sender.is_some(),
+            initialized: serialport_info.initialized,
+        })
+    })
+}
+
+/// `read` Read serial port
+///
+/// With `batch_ms` the data received within that many milliseconds of the first byte is emitted
+/// as a single event, time stamped when the first byte was read, trading latency for fewer
+/// events. Nothing is emitted for a window without data.
+///
+/// With `max_pending_events` every event has to be acknowledged with `acknowledge_read`. While
+/// that many are pending, received data is dropped and counted in the statistics.
+///
+/// With `silence_timeout_ms` the loop emits `plugin-serialport-silence-{path}` once nothing was
+/// received for that long, again only after data arrived in between. It is checked between reads,
+/// so it fires up to `timeout` late.
+///
+/// The frames split off by `delimiter` that arrive in one read are emitted together as a single
+/// event, with `emit_per_packet` set to `true` each of them is emitted as its own event. Other
+/// framing always emits one event per frame.
+#[command]
+pub fn read<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    timeout: Option<u64>,
+    size: Option<usize>,
+    read_encoding: Option<String>,
+    delimiter: Option<Vec<u8>>,
+    packet_size: Option<usize>,
+    max_read_errors: Option<u32>,
+    event_name: Option<String>,
+    target_window: Option<String>,
+    framing: Option<String>,
+    length_field_size: Option<u8>,
+    endian: Option<String>,
+    crc: Option<String>,
+    batch_ms: Option<u64>,
+    max_pending_events: Option<usize>,
+    silence_timeout_ms: Option<u64>,
+    emit_per_packet: Option<bool>,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    let options = ReadOptions {
+        timeout,
+        size: size.or(state.config.default_read_buffer_size),
+        read_encoding,
+        delimiter,
+        packet_size,
+        max_read_errors,
+        event_name,
+        target_window,
+        framing,
+        length_field_size,
+        endian,
+        crc,
+        batch_ms,
+        max_pending_events,
+        silence_timeout_ms,
+        emit_per_packet,
+    };
+    let serialports = state.serialports.clone();
+    let mut map = state.serialports.lock().map_err(|_| Error::LockPoisoned)?;
+    if options.event_name.is_some() {
+        let event_name = read_event_name(&path, &options);
+        // another reading port emitting under the same name would mix both data streams
+        if let Some((other_path, _)) = map.iter().find(|(other_path, other_info)| {
+            **other_path != path
+                && other_info.sender.is_some()
+                && other_info
+                    .read_options
+                    .as_ref()
+                    .map(|other_options| read_event_name(other_path, other_options))
+                    == Some(event_name.clone())
+        }) {
+            return Err(Error::InvalidArgument(format!(
+                "Event name {} is already used by serial port {}",
+                event_name, other_path
+            )));
+        }
+    }
+    match map.get_mut(&path) {
+        Some(serialport_info) => start_event_read(
+            serialport_info,
+            window,
+            &state.pool,
+            serialports,
+            path,
+            options,
+        ),
+        None => Err(Error::PortNotFound(path)),
+    }
+}
+
+/// `read_carrier_detect` Read the state of the carrier detect (CD) control signal
+#[command]
+pub fn read_carrier_detect<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<bool, Error> {
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .read_carrier_detect()
+    {
+        Ok(level) => Ok(level),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error reading carrier detect of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })
+}
+
+/// `read_clear_to_send` Read the state of the clear to send (CTS) control signal
+#[command]
+pub fn read_clear_to_send<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<bool, Error> {
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .read_clear_to_send()
+    {
+        Ok(level) => Ok(level),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error reading clear to send of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })
+}
+
+/// `read_data_set_ready` Read the state of the data set ready (DSR) control signal
+#[command]
+pub fn read_data_set_ready<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<bool, Error> {
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .read_data_set_ready()
+    {
+        Ok(level) => Ok(level),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error reading data set ready of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })
+}
+
+/// `read_discard` Read and drop everything waiting in the receive buffer without emitting events
+///
+/// Unlike `clear_input_buffer`, which asks the driver to clear its buffer, this reads until
+/// `bytes_to_read` reports nothing left, e.g. to resynchronize after a protocol error. Returns
+/// the number of bytes discarded.
+#[command]
+pub fn read_discard<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        if !serialport_info.access_mode.can_read() {
+            return Err(Error::AccessDenied {
+                path: path.clone(),
+                operation: "reading",
+            });
+        }
+        let mut buf = [0; 1024];
+        let mut discarded = 0;
+        loop {
+            let available = serialport_info
+                .serialport
+                .bytes_to_read()
+                .map_err(|error| {
+                    Error::SerialError(
+                        error.kind,
+                        format!(
+                            "Error getting bytes to read of serial port {}: {}",
+                            &path, error.description
+                        ),
+                    )
+                })?;
+            if available == 0 {
+                return Ok(discarded);
+            }
+            let size = (available as usize).min(buf.len());
+            match serialport_info.serialport.read(&mut buf[..size]) {
+                Ok(0) => return Ok(discarded),
+                Ok(size) => discarded += size,
+                Err(error) if error.kind() == io::ErrorKind::TimedOut => return Ok(discarded),
+                Err(error) => {
+                    return Err(Error::IoError(format!(
+                        "Error reading serial port {}: {}",
+                        &path, error
+                    )))
+                }
+            }
+        }
+    })
+}
+
+/// `read_ring_indicator` Read the state of the ring indicator (RI) control signal
+#[command]
+pub fn read_ring_indicator<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<bool, Error> {
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .read_ring_indicator()
+    {
+        Ok(level) => Ok(level),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error reading ring indicator of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })
+}
+
+/// `release_lease` Release a lease taken by `acquire_lease`
+///
+/// Does nothing if the lease already expired or was replaced by another one.
+#[command]
+pub fn release_lease<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    token: LeaseToken,
+) -> Result<(), Error> {
+    let mut leases = state.leases.lock().map_err(|_| Error::LockPoisoned)?;
+    if leases
+        .get(&token.path)
+        .map_or(false, |lease| lease.token == token.token)
+    {
+        leases.remove(&token.path);
+    }
+    Ok(())
+}
+
+/// `reopen` Close and open a serial port again with the settings it was opened with
+///
+/// Does nothing while the port still responds. A port that was being read is read again with
+/// the same options, emitting to the calling window, and bytes taken by `peek` are kept. Closing
+/// and opening the port run off the async runtime.
+///
+/// Emits `plugin-serialport-port-closed` to every window once the port is closed, and
+/// `plugin-serialport-port-opened` once it is open again. If the port cannot be opened it stays
+/// closed and the error says so.
+#[command]
+pub async fn reopen<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    let mut old_info = match state.serialports.lock() {
+        Ok(mut serialports) => {
+            let healthy = match serialports.get(&path) {
+                Some(serialport_info) => serialport_info.serialport.bytes_to_read().is_ok(),
+                None => return Err(Error::PortNotFound(path)),
+            };
+            if healthy {
+                return Ok(());
+            }
+            match serialports.remove(&path) {
+                Some(serialport_info) => serialport_info,
+                None => return Err(Error::PortNotFound(path)),
+            }
+        }
+        Err(_) => return Err(Error::LockPoisoned),
+    };
+    info!("Reopening serial port {}", &path);
+    let sender = old_info.sender.take();
+    let read_task = old_info.read_task.take();
+    let write_queue = old_info.write_queue.take();
+    let config = old_info.config.clone();
+    let recorder = state.recorder.clone();
+    let port_path = path.clone();
+    let reopened = tauri::async_runtime::spawn_blocking(move || {
+        let path = port_path;
+        // the old read loop holds a handle of its own, wait for it to give the port up
+        let stopped = stop_read(&path, sender, read_task);
+        let peeked = std::mem::take(&mut *peek::lock(&old_info.peek_buffer));
+        let read_options = old_info.read_options.take();
+        let reconnect = old_info.reconnect.take();
+        let (idle_timeout, access_mode, initialized) = (
+            old_info.idle_timeout,
+            old_info.access_mode,
+            old_info.initialized,
+        );
+        let hex_dump = old_info.hex_dump.clone();
+        drop(old_info);
+        stopped?;
+        let (serial, raw_fd) =
+            open_serial(get_port_builder(&path, &config)?, &config).map_err(|error| {
+                Error::SerialError(
+                    error.kind,
+                    format!("Error opening {}: {}", path, error.description),
+                )
+            })?;
+        let mut new_info = new_serialport_info(serial, &path, config, recorder.as_ref(), raw_fd);
+        *peek::lock(&new_info.peek_buffer) = peeked;
+        new_info.read_options = read_options;
+        new_info.idle_timeout = idle_timeout;
+        new_info.reconnect = reconnect;
+        new_info.access_mode = access_mode;
+        new_info.hex_dump = hex_dump;
+        new_info.initialized = initialized;
+        Ok::<_, Error>(new_info)
+    })
+    .await
+    .map_err(|error| Error::IoError(error.to_string()))
+    .and_then(|reopened| reopened);
+    emit_port_event(&app, "plugin-serialport-port-closed", &path);
+    let mut serialport_info = match reopened {
+        Ok(serialport_info) => serialport_info,
+        Err(error) => {
+            error!("Failed to reopen serial port {}: {}", &path, error);
+            return Err(closed_error(&path, error));
+        }
+    };
+    serialport_info.write_queue = write_queue;
+    let read_options = serialport_info.read_options.take();
+    let mut serialports = state.serialports.lock().map_err(|_| Error::LockPoisoned)?;
+    // another port opened at `path` meanwhile, this one is closed again when it is dropped here
+    check_vacant(&state.config, &serialports, &path)?;
+    let serialport_info = serialports.entry(path.clone()).or_insert(serialport_info);
+    let started = match read_options {
+        Some(read_options) => start_event_read(
+            serialport_info,
+            window,
+            &state.pool,
+            state.serialports.clone(),
+            path.clone(),
+            read_options,
+        ),
+        None => Ok(()),
+    };
+    drop(serialports);
+    emit_port_event(&app, "plugin-serialport-port-opened", &path);
+    started
+}
+
+/// `closed_error` `error` of a `reopen` of the port at `path`, noting that the port was closed
+fn closed_error(path: &str, error: Error) -> Error {
+    let note = format!("serial port {} was closed and could not be reopened", path);
+    match error {
+        Error::SerialError(kind, message) => {
+            Error::SerialError(kind, format!("{}, {}", message, note))
+        }
+        error => Error::IoError(format!("{}, {}", error, note)),
+    }
+}
+
+/// `request_port` Let the user pick one of the ports matching any of `filters`, like
+/// `navigator.serial.requestPort()` of the Web Serial API
+///
+/// With the `dialog` feature every matching port is offered in a native yes/no dialog on top of
+/// the calling window until one is accepted. Without it the only matching port is returned, and
+/// several matches fail with `InvalidArgument`. Fails with `NoPortSelected` if no port matches
+/// or the user declines all of them.
+#[command]
+pub async fn request_port<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    filters: Option<Vec<PortFilter>>,
+) -> Result<String, Error> {
+    let ports: Vec<SerialPortDetail> = available_ports_info()
+        .into_iter()
+        .filter(|port| match &filters {
+            Some(filters) if !filters.is_empty() => {
+                filters.iter().any(|filter| filter.matches(port))
+            }
+            _ => true,
+        })
+        .collect();
+    #[cfg(feature = "dialog")]
+    for port in ports {
+        let description = match (&port.usb_manufacturer, &port.usb_product) {
+            (Some(manufacturer), Some(product)) => {
+                format!("{} ({} {})", port.port_name, manufacturer, product)
+            }
+            (_, Some(product)) => format!("{} ({})", port.port_name, product),
+            _ => port.port_name.clone(),
+        };
+        if tauri::api::dialog::blocking::ask(
+            Some(&window),
+            "Select serial port",
+            format!("Connect to {}?", description),
+        ) {
+            return Ok(port.port_name);
+        }
+    }
+    #[cfg(not(feature = "dialog"))]
+    {
+        let _ = window;
+        if ports.len() > 1 {
+            return Err(Error::InvalidArgument(format!(
+                "{} serial ports match, enable the dialog feature to let the user pick one",
+                ports.len()
+            )));
+        }
+        if let Some(port) = ports.into_iter().next() {
+            return Ok(port.port_name);
+        }
+    }
+    Err(Error::NoPortSelected)
+}
+
+/// `reset_statistics` Reset the read and write counters of an open serial port
+#[command]
+pub fn reset_statistics<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    get_serialport(state, path, |serialport_info| {
+        serialport_info.counters.reset();
+        Ok(())
+    })
+}
+
+/// Baud rates tried by `scan_baud_rate`, in order.
+const SCAN_BAUD_RATES: [u32; 10] = [
+    300, 1200, 2400, 4800, 9600, 19200, 38400, 57600, 115200, 230400,
+];
+
+/// `scan_baud_rate` Find the baud rate a device answers at
+///
+/// Opens the port at each of `SCAN_BAUD_RATES` with 8 data bits, no parity and one stop bit,
+/// writes `test_payload` and waits up to `timeout_ms` for a response starting with
+/// `expected_response_prefix`. The port is closed after every attempt and must not be open.
+/// Returns the first matching rate, or `None`. The attempts block, so they run off the async
+/// runtime.
+///
+/// The port is not registered while it is scanned. An `open` of the same path fails while an
+/// attempt holds the port, and one slipping in between two attempts makes the next attempt, and
+/// with it the scan, fail.
+#[command]
+pub async fn scan_baud_rate<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    test_payload: Vec<u8>,
+    expected_response_prefix: Vec<u8>,
+    timeout_ms: u64,
+) -> Result<Option<u32>, Error> {
+    check_allowed_port(&state.config, &path)?;
+    if expected_response_prefix.is_empty() {
+        return Err(Error::InvalidArgument(
+            "Expected response prefix cannot be empty".to_string(),
+        ));
+    }
+    if state
+        .serialports
+        .lock()
+        .map_err(|_| Error::LockPoisoned)?
+        .contains_key(&path)
+    {
+        return Err(Error::AlreadyOpen(path));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        scan_baud_rates(&path, &test_payload, &expected_response_prefix, timeout_ms)
+    })
+    .await
+    .map_err(|error| Error::IoError(error.to_string()))?
+}
+
+/// `scan_baud_rates` try each of `SCAN_BAUD_RATES` for `scan_baud_rate`
+fn scan_baud_rates(
+    path: &str,
+    test_payload: &[u8],
+    expected_response_prefix: &[u8],
+    timeout_ms: u64,
+) -> Result<Option<u32>, Error> {
+    let counters = SerialportCounters::default();
+    for baud_rate in SCAN_BAUD_RATES {
+        let config = SerialportConfig::new(
+            baud_rate,
+            DataBits::Eight,
+            FlowControl::None,
+            Parity::None,
+            StopBits::One,
+            Duration::from_millis(timeout_ms.min(100)),
+        );
+        let (mut serial, _) =
+            open_serial(get_port_builder(path, &config)?, &config).map_err(|error| {
+                Error::SerialError(
+                    error.kind,
+                    format!("Error opening {}: {}", path, error.description),
+                )
+            })?;
+        if let Err(error) = serial.write_all(test_payload) {
+            return Err(Error::IoError(format!(
+                "Error writing to serial port {}: {}",
+                path, error
+            )));
+        }
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        // a wrong baud rate garbles the response or times out, both just mean trying the next
+        if let Ok(response) = read_response(
+            serial.as_mut(),
+            &counters,
+            path,
+            deadline,
+            Some(expected_response_prefix.len()),
+            &[],
+        ) {
+            if response == expected_response_prefix {
+                info!("Serial port {} answers at {} baud", path, baud_rate);
+                return Ok(Some(baud_rate));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// `send_break` Assert the BREAK condition for `duration_ms` milliseconds (default 100)
+///
+/// The shortest break the hardware can produce is platform-dependent, so very short durations
+/// may be stretched by the driver.
+#[command]
+pub async fn send_break<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    duration_ms: Option<u64>,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    let serial = get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .try_clone()
+    {
+        Ok(serial) => Ok(serial),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error sending break to serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })?;
+    if let Err(error) = serial.set_break() {
+        return Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error sending break to serial port {}: {}",
+                &path, error.description
+            ),
+        ));
+    }
+    sleep(Duration::from_millis(duration_ms.unwrap_or(100))).await;
+    match serial.clear_break() {
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error clearing break of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    }
+}
+
+/// XOFF, asks the remote side to pause sending.
+const XOFF: u8 = 0x13;
+
+/// XON, asks the remote side to resume sending.
+const XON: u8 = 0x11;
+
+/// `send_xoff` Write the XOFF character to pause the remote transmitter
+///
+/// Sent regardless of the `flow_control` of the port, for remote devices using software flow
+/// control while the driver is set to none.
+#[command]
+pub fn send_xoff<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_all_bytes(serialport_info, &path, &[XOFF], None)
+    })
+}
+
+/// `send_xon` Write the XON character to resume the remote transmitter, see `send_xoff`
+#[command]
+pub fn send_xon<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_all_bytes(serialport_info, &path, &[XON], None)
+    })
+}
+
+/// `set_baud_rate` Change the baud rate of an open serial port without closing it
+#[command]
+pub fn set_baud_rate<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    baud_rate: u32,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .set_baud_rate(baud_rate)
+    {
+        Ok(_) => {
+            serialport_info.config.baud_rate = baud_rate;
+            Ok(())
+        }
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error setting baud rate of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })
+}
+
+/// `set_hex_dump` Log everything the port reads and writes as a hex dump, at the info level
+///
+/// Meant for debugging protocol drivers, it is disabled when the port is opened.
+#[command]
+pub fn set_hex_dump<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    enabled: bool,
+) -> Result<(), Error> {
+    get_serialport(state, path, |serialport_info| {
+        serialport_info.hex_dump.store(enabled, Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+/// `start_port_monitor` Watch for serial ports being plugged in or removed
+///
+/// Polls the port list every `interval_ms` milliseconds (default 1000) and emits
+/// `plugin-serialport-port-added` and `plugin-serialport-port-removed` to all windows.
+#[command]
+pub fn start_port_monitor<R: Runtime>(
+    app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    interval_ms: Option<u64>,
+) -> Result<(), Error> {
+    let mut port_monitor = state.port_monitor.lock().map_err(|_| Error::LockPoisoned)?;
+    if port_monitor.is_some() {
+        warn!("Port monitor is already running!");
+        return Ok(());
+    }
+    let interval = Duration::from_millis(interval_ms.unwrap_or(1000));
+    let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
+    *port_monitor = Some(tx);
+    thread::spawn(move || {
+        let mut known = port_names();
+        loop {
+            match rx.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => {}
+                _ => {
+                    debug!("Port monitor stopped!");
+                    break;
+                }
+            }
+            let current = port_names();
+            for (event, ports) in [
+                ("plugin-serialport-port-added", current.difference(&known)),
+                ("plugin-serialport-port-removed", known.difference(&current)),
+            ] {
+                for port_name in ports {
+                    let payload = PortEvent {
+                        port_name: port_name.clone(),
+                    };
+                    if let Err(error) = app.emit_all(event, payload) {
+                        error!("Failed to send port event: {}", error);
+                    }
+                }
+            }
+            known = current;
+        }
+    });
+    Ok(())
+}
+
+/// `stop_port_monitor` Stop the watcher started by `start_port_monitor`
+#[command]
+pub fn stop_port_monitor<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+) -> Result<(), Error> {
+    let mut port_monitor = state.port_monitor.lock().map_err(|_| Error::LockPoisoned)?;
+    if let Some(sender) = port_monitor.take() {
+        let _ = sender.send(1);
+    }
+    Ok(())
+}
+
+/// `supported_baud_rates` Get the standard baud rates the driver of an open serial port accepts
+///
+/// The OS has no portable query for this, so each rate is set on the port in turn before its
+/// baud rate is restored. That would disturb transfers in progress, so it fails with
+/// `InvalidArgument` while the port is being read or has a write queue. Drivers accepting any
+/// rate, as on Linux and macOS, report every standard rate; other rates may work as well.
+#[command]
+pub fn supported_baud_rates<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<Vec<u32>, Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        if serialport_info.sender.is_some() || serialport_info.write_queue.is_some() {
+            return Err(Error::InvalidArgument(format!(
+                "Serial port {} is in use, cannot try baud rates on it",
+                path
+            )));
+        }
+        let serialport = serialport_info.serialport.as_mut();
+        let supported = STANDARD_BAUD_RATES
+            .iter()
+            .copied()
+            .filter(|&baud_rate| serialport.set_baud_rate(baud_rate).is_ok())
+            .collect();
+        match serialport.set_baud_rate(serialport_info.config.baud_rate) {
+            Ok(_) => Ok(supported),
+            Err(error) => Err(Error::SerialError(
+                error.kind,
+                format!(
+                    "Error restoring baud rate of serial port {}: {}",
+                    &path, error.description
+                ),
+            )),
+        }
+    })
+}
+
+/// `transaction` Write binary data to serial port and wait for the response
+///
+/// The response ends after `response_size` bytes or with `delimiter`, which is included. With
+/// neither, everything received within `response_timeout_ms` is returned, otherwise the
+/// transaction fails when the response is not complete by then. A running read loop is stopped
+/// while waiting, so it cannot take the response, and restarted afterwards emitting to the calling
+/// window.
+#[command]
+pub async fn transaction<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    payload: Vec<u8>,
+    response_timeout_ms: u64,
+    response_size: Option<usize>,
+    delimiter: Option<Vec<u8>>,
+) -> Result<Vec<u8>, Error> {
+    check_lease(&state, &path, &window)?;
+    match (response_size, &delimiter) {
+        (Some(0), _) => {
+            return Err(Error::InvalidArgument(
+                "Response size cannot be 0!".to_string(),
+            ))
+        }
+        (_, Some(delimiter)) if delimiter.is_empty() => {
+            return Err(Error::InvalidArgument(
+                "Delimiter cannot be empty!".to_string(),
+            ))
+        }
+        _ => {}
+    }
+    exchange(
+        window,
+        state,
+        path,
+        payload,
+        Duration::from_millis(response_timeout_ms),
+        response_size,
+        delimiter.into_iter().collect(),
+    )
+    .await
+}
+
+/// `exchange` write `payload` to the port at `path` and read the response with `read_response`
+///
+/// A running read loop is stopped meanwhile and restarted afterwards emitting to `window`, also
+/// when the exchange fails. Stopping the loop, writing and reading block, so they run off the
+/// async runtime.
+async fn exchange<R: Runtime>(
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    payload: Vec<u8>,
+    response_timeout: Duration,
+    response_size: Option<usize>,
+    delimiters: Vec<Vec<u8>>,
+) -> Result<Vec<u8>, Error> {
+    let (mut serial, counters, sender, read_task, read_options) =
+        get_serialport(state.clone(), path.clone(), |serialport_info| {
+            if !serialport_info.access_mode.can_read() {
+                return Err(Error::AccessDenied {
+                    path: path.clone(),
+                    operation: "reading",
+                });
+            }
+            let serial = serialport_info.serialport.try_clone().map_err(|error| {
+                Error::SerialError(
+                    error.kind,
+                    format!("Serial port {} read error: {}", &path, error.description),
+                )
+            })?;
+            let active = serialport_info.sender.is_some();
+            Ok((
+                serial,
+                serialport_info.counters.clone(),
+                serialport_info.sender.take(),
+                serialport_info.read_task.take(),
+                serialport_info.read_options.clone().filter(|_| active),
+            ))
+        })?;
+    let serialports = state.serialports.clone();
+    let exchange_path = path.clone();
+    let response = tauri::async_runtime::spawn_blocking(move || {
+        let path = exchange_path;
+        stop_read(&path, sender, read_task)?;
+        write_paced(&serialports, &path, &payload, None)?;
+        let delimiters: Vec<&[u8]> = delimiters.iter().map(Vec::as_slice).collect();
+        read_response(
+            serial.as_mut(),
+            &counters,
+            &path,
+            Instant::now() + response_timeout,
+            response_size,
+            &delimiters,
+        )
+    })
+    .await
+    .map_err(|error| Error::IoError(error.to_string()))
+    .and_then(|response| response);
+    if let Some(read_options) = read_options {
+        let serialports = state.serialports.clone();
+        get_serialport(state.clone(), path.clone(), |serialport_info| {
+            start_event_read(
+                serialport_info,
+                window,
+                &state.pool,
+                serialports,
+                path.clone(),
+                read_options,
+            )
+        })?;
+    }
+    response
+}
+
+/// `update_settings` Change the settings of an open serial port without closing it
+///
+/// Settings left out keep their current value. If one of them cannot be applied the previous
+/// settings are restored. A running read loop keeps going, but reads with the timeout it was
+/// started with.
+#[command]
+pub fn update_settings<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    baud_rate: Option<u32>,
+    data_bits: Option<usize>,
+    flow_control: Option<String>,
+    parity: Option<String>,
+    stop_bits: Option<usize>,
+    timeout: Option<u64>,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    let flow_control = match flow_control {
+        Some(value) => Some(get_flow_control(Some(value))?),
+        None => None,
+    };
+    let parity = match parity {
+        Some(value) => Some(get_parity(Some(value))?),
+        None => None,
+    };
+    get_serialport(state, path.clone(), |serialport_info| {
+        let old_config = serialport_info.config.clone();
+        let mut config = SerialportConfig::new(
+            baud_rate.unwrap_or(old_config.baud_rate),
+            get_data_bits(data_bits.or(Some(old_config.data_bits as usize))),
+            match flow_control {
+                Some(flow_control) => flow_control,
+                None => get_flow_control(Some(old_config.flow_control.clone()))?,
+            },
+            match parity {
+                Some(parity) => parity,
+                None => get_parity(Some(old_config.parity.clone()))?,
+            },
+            get_stop_bits(stop_bits.or(Some(old_config.stop_bits as usize))),
+            Duration::from_millis(timeout.unwrap_or(old_config.timeout_ms)),
+        );
+        config.inter_char_timeout_ms = old_config.inter_char_timeout_ms;
+        let serialport = serialport_info.serialport.as_mut();
+        match apply_config(serialport, &path, &config) {
+            Ok(()) => {
+                serialport_info.config = config;
+                Ok(())
+            }
+            Err(error) => {
+                if let Err(rollback_error) = apply_config(serialport, &path, &old_config) {
+                    error!("Failed to restore settings: {}", rollback_error);
+                }
+                Err(error)
+            }
+        }
+    })
+}
+
+/// `windows_friendly_name` Get the name Windows shows for a port, e.g. `USB Serial Port (COM3)`
+///
+/// Read from the device registry, `None` if the port has none. Fails with `Unsupported` on
+/// other platforms.
+#[command]
+pub fn windows_friendly_name(port_name: String) -> Result<Option<String>, Error> {
+    #[cfg(windows)]
+    {
+        let port_name = port_name.trim_start_matches(r"\\.\").to_uppercase();
+        Ok(crate::registry::friendly_names().remove(&port_name))
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = port_name;
+        Err(Error::Unsupported(
+            "Friendly port names are only stored on Windows".to_string(),
+        ))
+    }
+}
+
+/// `write` Write to serial port
+///
+/// The value is written as-is, no line ending is appended; use `write_line` for that.
+/// Fails with `PartialWrite` if the port accepts only part of the value, use `write_all` when
+/// every byte has to be delivered.
+#[command]
+pub fn write<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: String,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_bytes(serialport_info, &path, value.as_bytes())
+    })
+}
+
+/// `write_all` Write to serial port, retrying partial writes until every byte is written
+#[command]
+pub fn write_all<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: String,
+    max_attempts: Option<u32>,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_all_bytes(serialport_info, &path, value.as_bytes(), max_attempts)
+    })
+}
+
+/// `write_base64` Write a standard base64 encoded string to serial port as binary data
+#[command]
+pub fn write_base64<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: String,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    let data = match BASE64.decode(value.as_bytes()) {
+        Ok(data) => data,
+        Err(error) => {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid base64 string: {}",
+                error
+            )));
+        }
+    };
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_bytes(serialport_info, &path, &data)
+    })
+}
+
+/// `write_binary` Write binary data to serial port
+///
+/// Fails with `PartialWrite` if the port accepts only part of the data, use `write_binary_all`
+/// when every byte has to be delivered.
+#[command]
+pub fn write_binary<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_bytes(serialport_info, &path, &value)
+    })
+}
+
+/// `write_binary_all` Write binary data to serial port, retrying partial writes until every byte is written
+#[command]
+pub fn write_binary_all<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+    max_attempts: Option<u32>,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_all_bytes(serialport_info, &path, &value, max_attempts)
+    })
+}
+
+/// `write_binary_slice` Write `length` bytes of binary data starting at `offset`
+///
+/// Fails with `InvalidArgument` if the range is out of bounds, and with `PartialWrite` like
+/// `write_binary`.
+#[command]
+pub fn write_binary_slice<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+    offset: usize,
+    length: usize,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    let slice = match offset.checked_add(length) {
+        Some(end) if end <= value.len() => &value[offset..end],
+        _ => {
+            return Err(Error::InvalidArgument(format!(
+                "Slice of {} bytes at offset {} is out of bounds of {} bytes",
+                length,
+                offset,
+                value.len()
+            )));
+        }
+    };
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_bytes(serialport_info, &path, slice)
+    })
+}
+
+/// `write_binary_with_drain` Write binary data to serial port and wait until it has been transmitted
+#[command]
+pub fn write_binary_with_drain<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        let size = write_bytes(serialport_info, &path, &value)?;
+        flush_port(serialport_info, &path)?;
+        Ok(size)
+    })
 }
 
-fn get_parity(value: Option<String>) -> Parity {
-    match value {
-        Some(value) => match value.as_str() {
-            "Odd" => Parity::Odd,
-            "Even" => Parity::Even,
-            _ => Parity::None,
-        },
-        None => Parity::None,
+/// `write_chunks` Write binary data in `chunk_size` byte pieces, waiting `delay_ms` between them
+///
+/// Paces data for receivers with small buffers. The port is only locked while a piece is
+/// written, so other commands can use it during the delays. Returns the number of bytes written.
+#[command]
+pub async fn write_chunks<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+    chunk_size: usize,
+    delay_ms: u64,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    if chunk_size == 0 {
+        return Err(Error::InvalidArgument(
+            "Invalid chunk size 0, expected at least 1 byte".to_string(),
+        ));
+    }
+    let mut written = 0;
+    for (index, chunk) in value.chunks(chunk_size).enumerate() {
+        if index > 0 {
+            sleep(Duration::from_millis(delay_ms)).await;
+        }
+        get_serialport(state.clone(), path.clone(), |serialport_info| {
+            write_all_bytes(serialport_info, &path, chunk, None)
+        })?;
+        written += chunk.len();
     }
+    Ok(written)
 }
 
-fn get_stop_bits(value: Option<usize>) -> StopBits {
-    match value {
-        Some(value) => match value {
-            1 => StopBits::One,
-            2 => StopBits::Two,
-            _ => StopBits::Two,
-        },
-        None => StopBits::Two,
-    }
+/// `write_cobs` COBS-encode binary data and write it to serial port followed by a `0x00` delimiter
+///
+/// Returns the number of bytes written, fails with `PartialWrite` like `write_binary`.
+#[command]
+pub fn write_cobs<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    let mut frame = cobs_encode(&value);
+    frame.push(0);
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_bytes(serialport_info, &path, &frame)
+    })
 }
 
-/// `available_ports` Get the list of serial ports
+/// `write_data_terminal_ready` Set the level of the data terminal ready (DTR) control signal
 #[command]
-pub fn available_ports() -> Vec<String> {
-    let mut list = match serialport::available_ports() {
-        Ok(list) => list,
-        Err(_) => vec![],
-    };
-    list.sort_by(|a, b| a.port_name.cmp(&b.port_name));
+pub fn write_data_terminal_ready<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    level: bool,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .write_data_terminal_ready(level)
+    {
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error setting data terminal ready of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })
+}
 
-    let mut name_list: Vec<String> = vec![];
-    for i in &list {
-        name_list.push(i.port_name.clone());
-    }
+/// `write_hex` Write a hex encoded string such as `"A5 01 FF"` to serial port as binary data
+#[command]
+pub fn write_hex<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: String,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    let data = decode_hex(&value)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_bytes(serialport_info, &path, &data)
+    })
+}
 
-    println!("Serial ports: {:?}", &name_list);
+/// `write_line` Write to serial port followed by `line_ending` (default `"\r\n"`)
+#[command]
+pub fn write_line<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: String,
+    line_ending: Option<String>,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    let mut data = value.into_bytes();
+    data.extend_from_slice(line_ending.as_deref().unwrap_or("\r\n").as_bytes());
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_bytes(serialport_info, &path, &data)
+    })
+}
 
-    name_list
+/// `write_modbus_rtu` Write a MODBUS RTU request frame for `address` and `function_code`
+///
+/// `data` follows the function code and is itself followed by the CRC-16 of the frame, least
+/// significant byte first. Returns the size of the frame, fails with `PartialWrite` like
+/// `write_binary`.
+#[command]
+pub fn write_modbus_rtu<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    address: u8,
+    function_code: u8,
+    data: Vec<u8>,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    let mut frame = [&[address, function_code][..], &data].concat();
+    frame.extend(Crc::Crc16Modbus.to_bytes(&frame));
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_bytes(serialport_info, &path, &frame)
+    })
 }
 
-/// `cacel_read` Cancel serial data reading
+/// `write_queued` Queue binary data to be written to serial port and return right away
+///
+/// Queued data is written in order by a thread of the port's own, waiting
+/// `inter_byte_delay_ms` between bytes when it is set. Returns the number of writes waiting,
+/// including this one. Errors are reported by `flush_write_queue`.
 #[command]
-pub async fn cancel_read<R: Runtime>(
+pub fn write_queued<R: Runtime>(
     _app: AppHandle<R>,
-    _window: Window<R>,
+    window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
-) -> Result<(), Error> {
+    value: Vec<u8>,
+    inter_byte_delay_ms: Option<u64>,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    let serialports = state.serialports.clone();
     get_serialport(state, path.clone(), |serialport_info| {
-        match &serialport_info.sender {
-            Some(sender) => match sender.send(1) {
-                Ok(_) => {}
-                Err(error) => {
-                    return Err(Error::String(format!("Error cancelling read: {}", error)));
-                }
-            },
-            None => {}
-        }
-        serialport_info.sender = None;
-        println!("Cancelling {} serial read", &path);
-        Ok(())
+        let queue = serialport_info
+            .write_queue
+            .get_or_insert_with(|| start_write_queue(serialports, path));
+        let pending = {
+            let mut writes = queue.writes.lock().map_err(|_| Error::LockPoisoned)?;
+            writes
+                .pending
+                .push_back((value, inter_byte_delay_ms.map(Duration::from_millis)));
+            writes.pending.len()
+        };
+        let _ = queue.sender.send(());
+        Ok(pending)
     })
 }
 
-/// `close` Close serial port
+/// `write_repeated` Write `byte` `count` times, e.g. `0x55` sequences for bus resets or to enter
+/// a bootloader
+///
+/// With `inter_byte_delay_ms` the bytes are written one by one with that delay in between, and
+/// the port is only locked while a byte is written. Returns the number of bytes written.
 #[command]
-pub fn close<R: Runtime>(
+pub async fn write_repeated<R: Runtime>(
     _app: AppHandle<R>,
-    _window: Window<R>,
+    window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
-) -> Result<(), Error> {
-    match state.serialports.lock() {
-        Ok(mut serialports) => {
-            if serialports.remove(&path).is_some() {
-                Ok(())
-            } else {
-                Err(Error::String(format!("Serial port {} is not opened!", &path)))
+    byte: u8,
+    count: usize,
+    inter_byte_delay_ms: Option<u64>,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    let block = [byte; 256];
+    let mut written = 0;
+    while written < count {
+        let size = match inter_byte_delay_ms {
+            Some(delay_ms) => {
+                if written > 0 {
+                    sleep(Duration::from_millis(delay_ms)).await;
+                }
+                1
             }
-        }
-        Err(error) => {
-            Err(Error::String(format!("Failed to acquire lock: {}", error)))
-        }
+            None => (count - written).min(block.len()),
+        };
+        get_serialport(state.clone(), path.clone(), |serialport_info| {
+            write_all_bytes(serialport_info, &path, &block[..size], None)
+        })?;
+        written += size;
     }
+    Ok(written)
 }
 
-/// `close_all` Close all serial ports
+/// `write_request_to_send` Set the level of the request to send (RTS) control signal
 #[command]
-pub fn close_all<R: Runtime>(
+pub fn write_request_to_send<R: Runtime>(
     _app: AppHandle<R>,
-    _window: Window<R>,
+    window: Window<R>,
     state: State<'_, SerialportState>,
+    path: String,
+    level: bool,
 ) -> Result<(), Error> {
-    match state.serialports.lock() {
-        Ok(mut map) => {
-            for serialport_info in map.values() {
-                if let Some(sender) = &serialport_info.sender {
-                    match sender.send(1) {
-                        Ok(_) => {}
-                        Err(error) => {
-                            println!("Error closing serial ports: {}", error);
-                            return Err(Error::String(format!("Error closing serial ports: {}", error)));
-                        }
-                    }
-                }
-            }
-            map.clear();
-            Ok(())
-        }
-        Err(error) => {
-            Err(Error::String(format!("Failed to acquire lock: {}", error)))
-        }
-    }
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .write_request_to_send(level)
+    {
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::SerialError(
+            error.kind,
+            format!(
+                "Error setting request to send of serial port {}: {}",
+                &path, error.description
+            ),
+        )),
+    })
 }
 
-/// `force_close` Force close serial port
+/// `write_u16_be` Write a 16-bit unsigned integer to serial port, most significant byte first
 #[command]
-pub fn force_close<R: Runtime>(
+pub fn write_u16_be<R: Runtime>(
     _app: AppHandle<R>,
-    _window: Window<R>,
+    window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
+    value: u16,
 ) -> Result<(), Error> {
-    match state.serialports.lock() {
-        Ok(mut map) => {
-            if let Some(serial) = map.get_mut(&path) {
-                if let Some(sender) = &serial.sender {
-                    match sender.send(1) {
-                        Ok(_) => {}
-                        Err(error) => {
-                            println!("Error force closing serial ports: {}", error);
-                            return Err(Error::String(format!("Error force closing serial ports: {}", error)));
-                        }
-                    }
-                }
-                map.remove(&path);
-                Ok(())
-            } else {
-                Ok(())
-            }
-        }
-        Err(error) => {
-            Err(Error::String(format!("Failed to acquire lock: {}", error)))
-        }
-    }
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_all_bytes(serialport_info, &path, &value.to_be_bytes(), None)
+    })
 }
 
-/// `open` Open serial port
+/// `write_u16_le` Write a 16-bit unsigned integer to serial port, least significant byte first
 #[command]
-pub fn open<R: Runtime>(
+pub fn write_u16_le<R: Runtime>(
     _app: AppHandle<R>,
+    window: Window<R>,
     state: State<'_, SerialportState>,
-    _window: Window<R>,
     path: String,
-    baud_rate: u32,
-    data_bits: Option<usize>,
-    flow_control: Option<String>,
-    parity: Option<String>,
-    stop_bits: Option<usize>,
-    timeout: Option<u64>,
+    value: u16,
 ) -> Result<(), Error> {
-    match state.serialports.lock() {
-        Ok(mut serialports) => {
-            if serialports.contains_key(&path) {
-                return Err(Error::String(format!("Serial port {} is already open!", path)));
-            }
-            match serialport::new(path.clone(), baud_rate)
-                .data_bits(get_data_bits(data_bits))
-                .flow_control(get_flow_control(flow_control))
-                .parity(get_parity(parity))
-                .stop_bits(get_stop_bits(stop_bits))
-                .timeout(Duration::from_millis(timeout.unwrap_or(200)))
-                .open()
-            {
-                Ok(serial) => {
-                    let data = SerialportInfo {
-                        serialport: serial,
-                        sender: None,
-                    };
-                    serialports.insert(path, data);
-                    Ok(())
-                }
-                Err(error) => Err(Error::String(format!(
-                    "Error opening {}: {}",
-                    path,
-                    error.description
-                ))),
-            }
-        }
-        Err(error) => {
-            Err(Error::String(format!("Failed to acquire lock: {}", error)))
-        }
-    }
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_all_bytes(serialport_info, &path, &value.to_le_bytes(), None)
+    })
 }
 
-/// `read` Read serial port
+/// `write_u32_be` Write a 32-bit unsigned integer to serial port, most significant byte first
 #[command]
-pub fn read<R: Runtime>(
+pub fn write_u32_be<R: Runtime>(
     _app: AppHandle<R>,
     window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
-    timeout: Option<u64>,
-    size: Option<usize>,
+    value: u32,
 ) -> Result<(), Error> {
-    get_serialport(state.clone(), path.clone(), |serialport_info| {
-        if serialport_info.sender.is_some() {
-            println!("Serial port {} is already being read!", &path);
-            Ok(())
-        } else {
-            println!("Starting to read serial port {}!", &path);
-            match serialport_info.serialport.try_clone() {
-                Ok(mut serial) => {
-                    let read_event = format!("plugin-serialport-read-{}", &path);
-                    let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
-                    serialport_info.sender = Some(tx);
-                    thread::spawn(move || loop {
-                        match rx.try_recv() {
-                            Ok(_) => {
-                                println!("Done reading serial port {}!", &path);
-                                break;
-                            }
-                            Err(error) => match error {
-                                TryRecvError::Disconnected => {
-                                    println!("Serial port {} is disconnected!", &path);
-                                    break;
-                                }
-                                TryRecvError::Empty => {}
-                            },
-                        }
-                        let mut serial_buf: Vec<u8> = vec![0; size.unwrap_or(1024)];
-                        match serial.read(serial_buf.as_mut_slice()) {
-                            Ok(size) => {
-                                println!("Serial port {} read data: {}", &path, size);
-                                match window.emit(
-                                    &read_event,
-                                    ReadData {
-                                        data: &serial_buf[..size],
-                                        size,
-                                    },
-                                ) {
-                                    Ok(_) => {}
-                                    Err(error) => {
-                                        println!("Failed to send data: {}", error)
-                                    }
-                                }
-                            }
-                            Err(_err) => {
-                                // println!("Failed to read data! {:?}", err);
-                            }
-                        }
-                        thread::sleep(Duration::from_millis(timeout.unwrap_or(200)));
-                    });
-                }
-                Err(error) => {
-                    return Err(Error::String(format!("Serial port {} read error: {}", &path, error)));
-                }
-            }
-            Ok(())
-        }
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_all_bytes(serialport_info, &path, &value.to_be_bytes(), None)
     })
 }
 
-/// `write` Write to serial port
+/// `write_u32_le` Write a 32-bit unsigned integer to serial port, least significant byte first
 #[command]
-pub fn write<R: Runtime>(
+pub fn write_u32_le<R: Runtime>(
     _app: AppHandle<R>,
-    _window: Window<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: u32,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_all_bytes(serialport_info, &path, &value.to_le_bytes(), None)
+    })
+}
+
+/// `write_u8` Write a single byte to serial port
+#[command]
+pub fn write_u8<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: u8,
+) -> Result<(), Error> {
+    check_lease(&state, &path, &window)?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_all_bytes(serialport_info, &path, &value.to_be_bytes(), None)
+    })
+}
+
+/// `write_with_crc` Write binary data to serial port followed by its checksum
+///
+/// `algorithm` is one of `"crc8"`, `"crc16-ccitt"`, `"crc16-modbus"` or `"crc32"`, the checksum is
+/// appended most significant byte first except for CRC-16/MODBUS. Returns the number of bytes
+/// written including the checksum, fails with `PartialWrite` like `write_binary`.
+#[command]
+pub fn write_with_crc<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+    algorithm: String,
+) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
+    let crc = get_crc_algorithm(&algorithm)?;
+    let frame = [&value[..], &crc.to_bytes(&value)].concat();
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_bytes(serialport_info, &path, &frame)
+    })
+}
+
+/// `write_with_drain` Write to serial port and wait until the data has been transmitted
+///
+/// Unlike `write_all`, which only waits for the port to accept the data, this returns once the
+/// OS transmit buffer is empty, e.g. before switching the direction of an RS-485 transceiver.
+#[command]
+pub fn write_with_drain<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
     value: String,
 ) -> Result<usize, Error> {
+    check_lease(&state, &path, &window)?;
     get_serialport(state, path.clone(), |serialport_info| {
-        match serialport_info.serialport.write(value.as_bytes()) {
-            Ok(size) => {
-                Ok(size)
-        }
-            Err(error) => {
-                Err(Error::String(format!(
-                    "Error writing to serial port {}: {}",
-                    &path, error
-                )))
-            }
-        }
+        let size = write_bytes(serialport_info, &path, value.as_bytes())?;
+        flush_port(serialport_info, &path)?;
+        Ok(size)
     })
 }
 
-/// `write` Write binary data to serial port 
+/// `write_with_encoding` Write text to serial port in the charset `encoding`, e.g. `"latin-1"`
+///
+/// Unlike `write`, which sends UTF-8, the text is transcoded for devices using an 8-bit charset.
+/// `encoding` is an IANA label such as `"latin-1"`, `"windows-1252"`, `"ascii"` or `"utf-8"`.
+/// With `strict` set to `true` characters the charset lacks fail with `InvalidArgument`,
+/// otherwise they are sent as `?`. Fails with `PartialWrite` like `write`.
 #[command]
-pub fn write_binary<R: Runtime>(
+pub fn write_with_encoding<R: Runtime>(
     _app: AppHandle<R>,
-    _window: Window<R>,
+    window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
-    value: Vec<u8>,
+    value: String,
+    encoding: String,
+    strict: Option<bool>,
 ) -> Result<usize, Error> {
-    get_serialport(state, path.clone(), |serialport_info| match serialport_info
-        .serialport
-        .write(&value)
-    {
-        Ok(size) => {
-            Ok(size)
-        }
-        Err(error) => {
-            Err(Error::String(format!(
-                "Error writing to serial port {}: {}",
-                &path, error
-            )))
-        }
+    check_lease(&state, &path, &window)?;
+    let data = encode_text(&value, &encoding, strict.unwrap_or(false))?;
+    get_serialport(state, path.clone(), |serialport_info| {
+        write_bytes(serialport_info, &path, &data)
     })
 }
@@ -0,0 +1,98 @@
+/// A checksum trailing a frame, sent most significant byte first except for CRC-16/MODBUS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crc {
+    /// CRC-8 with polynomial `0x07` and initial value `0x00`.
+    Crc8,
+    /// CRC-16/CCITT-FALSE with polynomial `0x1021` and initial value `0xFFFF`.
+    Crc16Ccitt,
+    /// CRC-16/MODBUS with the reflected polynomial `0xA001` and initial value `0xFFFF`, sent
+    /// least significant byte first.
+    Crc16Modbus,
+    /// CRC-32 as used by Ethernet and zlib.
+    Crc32,
+}
+
+impl Crc {
+    /// The algorithm called `name`, one of `"crc8"`, `"crc16-ccitt"`, `"crc16-modbus"` or
+    /// `"crc32"`.
+    pub fn from_name(name: &str) -> Option<Crc> {
+        match name {
+            "crc8" => Some(Crc::Crc8),
+            "crc16-ccitt" => Some(Crc::Crc16Ccitt),
+            "crc16-modbus" => Some(Crc::Crc16Modbus),
+            "crc32" => Some(Crc::Crc32),
+            _ => None,
+        }
+    }
+
+    /// Size in bytes of the checksum.
+    pub fn size(self) -> usize {
+        match self {
+            Crc::Crc8 => 1,
+            Crc::Crc16Ccitt | Crc::Crc16Modbus => 2,
+            Crc::Crc32 => 4,
+        }
+    }
+
+    pub fn compute(self, data: &[u8]) -> u32 {
+        match self {
+            Crc::Crc8 => data.iter().fold(0u8, |crc, &byte| {
+                (0..8).fold(crc ^ byte, |crc, _| {
+                    if crc & 0x80 != 0 {
+                        (crc << 1) ^ 0x07
+                    } else {
+                        crc << 1
+                    }
+                })
+            }) as u32,
+            Crc::Crc16Ccitt => data.iter().fold(0xFFFFu16, |crc, &byte| {
+                (0..8).fold(crc ^ ((byte as u16) << 8), |crc, _| {
+                    if crc & 0x8000 != 0 {
+                        (crc << 1) ^ 0x1021
+                    } else {
+                        crc << 1
+                    }
+                })
+            }) as u32,
+            Crc::Crc16Modbus => data.iter().fold(0xFFFFu16, |crc, &byte| {
+                (0..8).fold(crc ^ byte as u16, |crc, _| {
+                    if crc & 1 != 0 {
+                        (crc >> 1) ^ 0xA001
+                    } else {
+                        crc >> 1
+                    }
+                })
+            }) as u32,
+            Crc::Crc32 => !data.iter().fold(0xFFFF_FFFFu32, |crc, &byte| {
+                (0..8).fold(crc ^ byte as u32, |crc, _| {
+                    if crc & 1 != 0 {
+                        (crc >> 1) ^ 0xEDB8_8320
+                    } else {
+                        crc >> 1
+                    }
+                })
+            }),
+        }
+    }
+
+    /// The checksum of `data` in the byte order it is sent in.
+    pub fn to_bytes(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Crc::Crc16Modbus => self.compute(data).to_le_bytes()[..2].to_vec(),
+            _ => self.compute(data).to_be_bytes()[4 - self.size()..].to_vec(),
+        }
+    }
+
+    /// The payload of `frame` if its trailing checksum matches, `None` otherwise.
+    pub fn check(self, frame: &[u8]) -> Option<&[u8]> {
+        if frame.len() < self.size() {
+            return None;
+        }
+        let (payload, crc) = frame.split_at(frame.len() - self.size());
+        if self.to_bytes(payload) == crc {
+            Some(payload)
+        } else {
+            None
+        }
+    }
+}
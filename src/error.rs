@@ -1,30 +1,106 @@
-use serde::{Serialize, Serializer};
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct InvokeResult {
     pub code: i32,
     pub message: String,
 }
 
 /// The error types.
+///
+/// Errors are sent to the frontend as `{ kind, message }` objects, where `kind` is the variant
+/// name, so callers can tell failures apart without parsing the message.
 #[derive(thiserror::Error, Debug)]
 #[non_exhaustive]
-pub enum Error {
-    /// JSON error.
-    // #[error(transparent)]
-    // Json(#[from] serde_json::Error),
+pub enum SerialportError {
+    /// The serial port is not open.
+    #[error("Serial port {0} not found")]
+    PortNotFound(String),
+    /// The serial port is already open.
+    #[error("Serial port {0} is already open!")]
+    AlreadyOpen(String),
     /// IO error.
-    #[error(transparent)]
-    Io(#[from] std::io::Error),
     #[error("{0}")]
-    String(String),
+    IoError(String),
+    /// The plugin state lock was poisoned by a panicking thread.
+    #[error("Failed to acquire lock")]
+    LockPoisoned,
+    /// An argument passed to a command is invalid.
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// The port was opened without access for `operation`, see the `access_mode` of `open`.
+    #[error("Serial port {path} is not open for {operation}")]
+    AccessDenied {
+        path: String,
+        operation: &'static str,
+    },
+    /// The path is not in `allowed_ports` of the plugin configuration.
+    #[error("Access to serial port {0} is denied")]
+    PermissionDenied(String),
+    /// `max_open_ports` of the plugin configuration are already open.
+    #[error("At most {0} serial ports can be open")]
+    TooManyPorts(usize),
+    /// Another window holds a lease on the port, see `acquire_lease`.
+    #[error("Serial port {0} is leased by another window")]
+    PortLeased(String),
+    /// `request_port` found no matching port or the user picked none.
+    #[error("No serial port selected")]
+    NoPortSelected,
+    /// The port accepted only `written` of `total` bytes in a single write.
+    #[error("Only {written} of {total} bytes were written")]
+    PartialWrite { written: usize, total: usize },
+    /// The operation is not supported on this platform.
+    #[error("{0}")]
+    Unsupported(String),
+    /// Error reported by the `serialport` crate.
+    #[error("{1}")]
+    SerialError(serialport::ErrorKind, String),
+}
+
+pub type Error = SerialportError;
+
+impl SerialportError {
+    /// The variant name sent to the frontend as `kind`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SerialportError::PortNotFound(_) => "PortNotFound",
+            SerialportError::AlreadyOpen(_) => "AlreadyOpen",
+            SerialportError::IoError(_) => "IoError",
+            SerialportError::LockPoisoned => "LockPoisoned",
+            SerialportError::InvalidArgument(_) => "InvalidArgument",
+            SerialportError::AccessDenied { .. } => "AccessDenied",
+            SerialportError::PermissionDenied(_) => "PermissionDenied",
+            SerialportError::TooManyPorts(_) => "TooManyPorts",
+            SerialportError::PortLeased(_) => "PortLeased",
+            SerialportError::NoPortSelected => "NoPortSelected",
+            SerialportError::PartialWrite { .. } => "PartialWrite",
+            SerialportError::Unsupported(_) => "Unsupported",
+            SerialportError::SerialError(..) => "SerialError",
+        }
+    }
+}
+
+impl From<std::io::Error> for SerialportError {
+    fn from(error: std::io::Error) -> Self {
+        SerialportError::IoError(error.to_string())
+    }
+}
+
+impl From<serialport::Error> for SerialportError {
+    fn from(error: serialport::Error) -> Self {
+        SerialportError::SerialError(error.kind, error.description)
+    }
 }
 
-impl Serialize for Error {
+impl Serialize for SerialportError {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        let mut error = serializer.serialize_struct("SerialportError", 2)?;
+        error.serialize_field("kind", self.kind())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.end()
     }
 }
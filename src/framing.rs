@@ -0,0 +1,357 @@
+use crate::crc::Crc;
+use std::time::{Duration, Instant};
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+/// Longest NMEA 0183 sentence accepted, the standard allows 82 bytes but receivers exceed it.
+const NMEA_MAX_LENGTH: usize = 256;
+
+/// Splits the bytes received by the `read` loop into complete messages.
+pub enum Framer {
+    /// Every chunk read from the port is passed on as it is.
+    None,
+    /// Messages end with, and include, `delimiter`.
+    Delimiter { delimiter: Vec<u8>, buffer: Vec<u8> },
+    /// Messages are exactly `size` bytes long.
+    PacketSize { size: usize, buffer: Vec<u8> },
+    /// SLIP frames (RFC 1055) end with `0xC0`, messages are the decoded frames.
+    ///
+    /// A frame with an invalid escape sequence is dropped and reported in `errors`.
+    Slip {
+        buffer: Vec<u8>,
+        escaped: bool,
+        invalid: bool,
+        errors: Vec<String>,
+    },
+    /// COBS frames end with `0x00`, messages are the decoded frames.
+    ///
+    /// A frame that is not valid COBS is dropped and reported in `errors`.
+    Cobs {
+        buffer: Vec<u8>,
+        errors: Vec<String>,
+    },
+    /// Messages start with their length as a `field_size` bytes integer, which is not emitted.
+    LengthPrefix {
+        field_size: usize,
+        big_endian: bool,
+        buffer: Vec<u8>,
+    },
+    /// MODBUS RTU frames end with a pause of at least `silence`, messages are the frames with a
+    /// valid CRC-16, which is kept.
+    ///
+    /// A frame with a wrong checksum is dropped and reported in `errors`.
+    ModbusRtu {
+        silence: Duration,
+        buffer: Vec<u8>,
+        last_read_at: Option<Instant>,
+        errors: Vec<String>,
+    },
+    /// NMEA 0183 sentences run from `$` to `\r\n`, messages are the sentences without the line
+    /// ending. Bytes outside of sentences are skipped.
+    ///
+    /// A sentence with a wrong `*XX` checksum is dropped and reported in `errors`.
+    Nmea {
+        buffer: Vec<u8>,
+        errors: Vec<String>,
+    },
+}
+
+impl Framer {
+    /// Feed newly read bytes and return the messages they complete, in order.
+    pub fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        match self {
+            Framer::None => vec![data.to_vec()],
+            Framer::Delimiter { delimiter, buffer } => {
+                // a delimiter can only end inside the new bytes, so skip what was already searched
+                let mut search_from = buffer.len().saturating_sub(delimiter.len() - 1);
+                buffer.extend_from_slice(data);
+                let mut frames = vec![];
+                let mut start = 0;
+                while let Some(position) = find(&buffer[search_from..], delimiter) {
+                    let end = search_from + position + delimiter.len();
+                    frames.push(buffer[start..end].to_vec());
+                    start = end;
+                    search_from = end;
+                }
+                buffer.drain(..start);
+                frames
+            }
+            Framer::PacketSize { size, buffer } => {
+                buffer.extend_from_slice(data);
+                let frames: Vec<Vec<u8>> = buffer.chunks_exact(*size).map(<[u8]>::to_vec).collect();
+                buffer.drain(..frames.len() * *size);
+                frames
+            }
+            Framer::Slip {
+                buffer,
+                escaped,
+                invalid,
+                errors,
+            } => {
+                let mut frames = vec![];
+                for &byte in data {
+                    if byte == SLIP_END {
+                        // empty frames are sent by many stacks to flush line noise, skip them
+                        if !*invalid && !buffer.is_empty() {
+                            frames.push(std::mem::take(buffer));
+                        }
+                        buffer.clear();
+                        *escaped = false;
+                        *invalid = false;
+                    } else if *invalid {
+                        // the rest of a dropped frame is skipped
+                    } else if *escaped {
+                        *escaped = false;
+                        match byte {
+                            SLIP_ESC_END => buffer.push(SLIP_END),
+                            SLIP_ESC_ESC => buffer.push(SLIP_ESC),
+                            _ => {
+                                errors.push(format!(
+                                    "Invalid SLIP escape sequence 0x{:02X} 0x{:02X}",
+                                    SLIP_ESC, byte
+                                ));
+                                *invalid = true;
+                            }
+                        }
+                    } else if byte == SLIP_ESC {
+                        *escaped = true;
+                    } else {
+                        buffer.push(byte);
+                    }
+                }
+                frames
+            }
+            Framer::Cobs { buffer, errors } => {
+                let mut frames = vec![];
+                for &byte in data {
+                    if byte != 0 {
+                        buffer.push(byte);
+                    } else if !buffer.is_empty() {
+                        match cobs_decode(buffer) {
+                            Some(frame) => frames.push(frame),
+                            None => {
+                                errors.push(format!("Invalid COBS frame of {} bytes", buffer.len()))
+                            }
+                        }
+                        buffer.clear();
+                    }
+                }
+                frames
+            }
+            Framer::LengthPrefix {
+                field_size,
+                big_endian,
+                buffer,
+            } => {
+                buffer.extend_from_slice(data);
+                let mut frames = vec![];
+                let mut start = 0;
+                while buffer.len() - start >= *field_size {
+                    let header = &buffer[start..start + *field_size];
+                    let length = if *big_endian {
+                        header
+                            .iter()
+                            .fold(0, |length, &byte| length << 8 | byte as usize)
+                    } else {
+                        header
+                            .iter()
+                            .rev()
+                            .fold(0, |length, &byte| length << 8 | byte as usize)
+                    };
+                    let end = start + *field_size + length;
+                    if buffer.len() < end {
+                        break;
+                    }
+                    frames.push(buffer[start + *field_size..end].to_vec());
+                    start = end;
+                }
+                buffer.drain(..start);
+                frames
+            }
+            Framer::ModbusRtu {
+                silence,
+                buffer,
+                last_read_at,
+                errors,
+            } => {
+                let now = Instant::now();
+                let mut frames = vec![];
+                if let Some(read_at) = *last_read_at {
+                    if now.duration_since(read_at) >= *silence {
+                        frames.extend(take_modbus_frame(buffer, errors));
+                    }
+                }
+                buffer.extend_from_slice(data);
+                *last_read_at = Some(now);
+                frames
+            }
+            Framer::Nmea { buffer, errors } => {
+                let mut frames = vec![];
+                for &byte in data {
+                    if byte == b'$' {
+                        buffer.clear();
+                    } else if buffer.is_empty() {
+                        continue;
+                    }
+                    buffer.push(byte);
+                    if buffer.ends_with(b"\r\n") {
+                        let sentence = std::mem::take(buffer);
+                        let sentence = &sentence[..sentence.len() - 2];
+                        match check_nmea_sentence(sentence) {
+                            Ok(()) => frames.push(sentence.to_vec()),
+                            Err(error) => errors.push(error),
+                        }
+                    } else if buffer.len() > NMEA_MAX_LENGTH {
+                        errors.push(format!(
+                            "NMEA sentence longer than {} bytes",
+                            NMEA_MAX_LENGTH
+                        ));
+                        buffer.clear();
+                    }
+                }
+                frames
+            }
+        }
+    }
+
+    /// Return the messages completed by the time passed since the last `push`.
+    pub fn poll(&mut self) -> Vec<Vec<u8>> {
+        match self {
+            Framer::ModbusRtu {
+                silence,
+                buffer,
+                last_read_at: Some(read_at),
+                errors,
+            } if read_at.elapsed() >= *silence => {
+                take_modbus_frame(buffer, errors).into_iter().collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Take the framing errors found since the last call, in order.
+    pub fn take_errors(&mut self) -> Vec<String> {
+        match self {
+            Framer::Slip { errors, .. }
+            | Framer::Cobs { errors, .. }
+            | Framer::ModbusRtu { errors, .. }
+            | Framer::Nmea { errors, .. } => std::mem::take(errors),
+            _ => vec![],
+        }
+    }
+
+    /// Whether the messages completed by one read are emitted together as a single event.
+    pub fn emits_combined(&self) -> bool {
+        match self {
+            Framer::None | Framer::Delimiter { .. } => true,
+            Framer::PacketSize { .. }
+            | Framer::Slip { .. }
+            | Framer::Cobs { .. }
+            | Framer::LengthPrefix { .. }
+            | Framer::ModbusRtu { .. }
+            | Framer::Nmea { .. } => false,
+        }
+    }
+}
+
+/// Take the buffered MODBUS RTU frame if it has a valid checksum, reporting it in `errors`
+/// otherwise.
+fn take_modbus_frame(buffer: &mut Vec<u8>, errors: &mut Vec<String>) -> Option<Vec<u8>> {
+    let frame = std::mem::take(buffer);
+    if frame.is_empty() {
+        None
+    } else if frame.len() < 4 {
+        errors.push(format!(
+            "MODBUS RTU frame of {} bytes is too short",
+            frame.len()
+        ));
+        None
+    } else if Crc::Crc16Modbus.check(&frame).is_none() {
+        errors.push(format!(
+            "MODBUS RTU frame of {} bytes has a wrong checksum",
+            frame.len()
+        ));
+        None
+    } else {
+        Some(frame)
+    }
+}
+
+/// Check the `*XX` checksum of an NMEA sentence without line ending, the XOR of the bytes
+/// between `$` and `*`. Sentences without checksum are accepted.
+fn check_nmea_sentence(sentence: &[u8]) -> Result<(), String> {
+    let star = match sentence.iter().rposition(|&byte| byte == b'*') {
+        Some(star) => star,
+        None => return Ok(()),
+    };
+    let expected = std::str::from_utf8(&sentence[star + 1..])
+        .ok()
+        .filter(|checksum| checksum.len() == 2)
+        .and_then(|checksum| u8::from_str_radix(checksum, 16).ok());
+    let actual = sentence[1..star]
+        .iter()
+        .fold(0, |checksum, &byte| checksum ^ byte);
+    match expected {
+        Some(expected) if expected == actual => Ok(()),
+        Some(expected) => Err(format!(
+            "NMEA sentence {} has checksum {:02X} instead of {:02X}",
+            String::from_utf8_lossy(sentence),
+            expected,
+            actual
+        )),
+        None => Err(format!(
+            "NMEA sentence {} has an invalid checksum",
+            String::from_utf8_lossy(sentence)
+        )),
+    }
+}
+
+/// Encode `data` with COBS, without the trailing `0x00` delimiter.
+pub fn cobs_encode(data: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(data.len() + data.len() / 254 + 1);
+    // each block starts with the offset of the next zero, patched once the block is complete
+    let mut code_index = 0;
+    let mut code: u8 = 1;
+    encoded.push(0);
+    for &byte in data {
+        if byte != 0 {
+            encoded.push(byte);
+            code += 1;
+        }
+        if byte == 0 || code == 0xFF {
+            encoded[code_index] = code;
+            code_index = encoded.len();
+            code = 1;
+            encoded.push(0);
+        }
+    }
+    encoded[code_index] = code;
+    encoded
+}
+
+/// Decode a COBS `frame` received without its `0x00` delimiter, `None` if it is not valid.
+pub fn cobs_decode(frame: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(frame.len());
+    let mut index = 0;
+    while index < frame.len() {
+        let code = frame[index] as usize;
+        if code == 0 || index + code > frame.len() {
+            return None;
+        }
+        decoded.extend_from_slice(&frame[index + 1..index + code]);
+        index += code;
+        // a full block of 254 bytes is not followed by a zero
+        if code < 0xFF && index < frame.len() {
+            decoded.push(0);
+        }
+    }
+    Some(decoded)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
@@ -0,0 +1,110 @@
+use crate::error::Error;
+use crate::state::{AccessMode, SerialportCounters, SerialportState};
+use serialport::SerialPort;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A clone of an open port for Rust code, returned by `SerialportState::get_handle`.
+///
+/// The handle reads and writes the port directly, without going through the commands. It
+/// shares the statistics and the access mode of the port, and keeps working until the port is
+/// closed by the plugin.
+pub struct SerialportHandle {
+    path: String,
+    serialport: Box<dyn SerialPort>,
+    access_mode: AccessMode,
+    counters: Arc<SerialportCounters>,
+}
+
+impl SerialportState {
+    /// Get a `SerialportHandle` of the open port at `path`.
+    pub fn get_handle(&self, path: &str) -> Result<SerialportHandle, Error> {
+        let serialports = self.serialports.lock().map_err(|_| Error::LockPoisoned)?;
+        let serialport_info = serialports
+            .get(path)
+            .ok_or_else(|| Error::PortNotFound(path.to_string()))?;
+        let serialport = serialport_info.serialport.try_clone().map_err(|error| {
+            Error::SerialError(
+                error.kind,
+                format!("Error cloning serial port {}: {}", path, error.description),
+            )
+        })?;
+        Ok(SerialportHandle {
+            path: path.to_string(),
+            serialport,
+            access_mode: serialport_info.access_mode,
+            counters: serialport_info.counters.clone(),
+        })
+    }
+}
+
+/// Read once from the open port at `path`, for Rust code that wants a plain blocking read.
+///
+/// The state is only locked to clone the port, so writes and commands go on while this waits up
+/// to `timeout_ms` for at most `buf_size` bytes. Returns the bytes read, none if nothing arrived
+/// in time.
+pub fn read_raw(
+    state: &SerialportState,
+    path: &str,
+    buf_size: usize,
+    timeout_ms: u64,
+) -> Result<Vec<u8>, Error> {
+    let mut handle = state.get_handle(path)?;
+    handle.check(handle.access_mode.can_read(), "reading")?;
+    // clones share their timeout on Windows, so the one of the port is restored afterwards
+    let timeout = handle.serialport.timeout();
+    handle
+        .serialport
+        .set_timeout(Duration::from_millis(timeout_ms))?;
+    let mut buf = vec![0; buf_size];
+    let result = handle.serialport.read(&mut buf);
+    handle.serialport.set_timeout(timeout)?;
+    let size = match result {
+        Ok(size) => size,
+        Err(error) if error.kind() == io::ErrorKind::TimedOut => 0,
+        Err(error) => return Err(error.into()),
+    };
+    handle.counters.record_read(size);
+    buf.truncate(size);
+    Ok(buf)
+}
+
+impl SerialportHandle {
+    /// Write `data` with a single write call, returning how many bytes the port accepted.
+    pub fn write(&mut self, data: &[u8]) -> Result<usize, Error> {
+        self.check(self.access_mode.can_write(), "writing")?;
+        let size = self.serialport.write(data)?;
+        self.counters.record_write(size);
+        Ok(size)
+    }
+
+    /// Read into `buf`, blocking until data arrives or the timeout of the port elapses.
+    pub fn read_blocking(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.check(self.access_mode.can_read(), "reading")?;
+        let size = self.serialport.read(buf)?;
+        self.counters.record_read(size);
+        Ok(size)
+    }
+
+    /// Wait until everything written has been sent.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        Ok(self.serialport.flush()?)
+    }
+
+    /// Flush and release the handle. The port stays open in the plugin until closed there.
+    pub fn close(mut self) -> Result<(), Error> {
+        self.flush()
+    }
+
+    fn check(&self, allowed: bool, operation: &'static str) -> Result<(), Error> {
+        if allowed {
+            Ok(())
+        } else {
+            Err(Error::AccessDenied {
+                path: self.path.clone(),
+                operation,
+            })
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::io;
+use std::time::Duration;
+
+/// Make reads return once no byte arrived for `timeout` after the first one, see `open`.
+///
+/// Sets `VTIME` with `VMIN` 1, so the timeout is rounded up to tenths of a second and capped at
+/// 25.5 seconds. The overall read timeout still applies while waiting for the first byte.
+#[cfg(unix)]
+pub fn set(port: &serialport::TTYPort, timeout: Duration) -> io::Result<()> {
+    use nix::sys::termios::{tcgetattr, tcsetattr, SetArg, SpecialCharacterIndices};
+    use std::os::unix::io::AsRawFd;
+
+    let raw_fd = port.as_raw_fd();
+    let mut termios = tcgetattr(raw_fd)?;
+    let tenths = ((timeout.as_millis() + 99) / 100).clamp(1, 255) as u8;
+    termios.control_chars[SpecialCharacterIndices::VMIN as usize] = 1;
+    termios.control_chars[SpecialCharacterIndices::VTIME as usize] = tenths;
+    tcsetattr(raw_fd, SetArg::TCSANOW, &termios)?;
+    Ok(())
+}
+
+/// Make reads return once no byte arrived for `timeout` after the first one, see `open`.
+///
+/// Sets `ReadIntervalTimeout` of `COMMTIMEOUTS`, keeping the overall read timeout of the port as
+/// a constant limit. Changing the timeout of the port afterwards drops the interval again.
+#[cfg(windows)]
+pub fn set(port: &serialport::COMPort, timeout: Duration) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Devices::Communication::{
+        GetCommTimeouts, SetCommTimeouts, COMMTIMEOUTS,
+    };
+
+    let handle = port.as_raw_handle() as windows_sys::Win32::Foundation::HANDLE;
+    let mut timeouts = COMMTIMEOUTS {
+        ReadIntervalTimeout: 0,
+        ReadTotalTimeoutMultiplier: 0,
+        ReadTotalTimeoutConstant: 0,
+        WriteTotalTimeoutMultiplier: 0,
+        WriteTotalTimeoutConstant: 0,
+    };
+    // SAFETY: the handle is open for as long as `port` is borrowed
+    if unsafe { GetCommTimeouts(handle, &mut timeouts) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    timeouts.ReadIntervalTimeout = timeout.as_millis().clamp(1, u32::MAX as u128 - 1) as u32;
+    timeouts.ReadTotalTimeoutMultiplier = 0;
+    // SAFETY: as above
+    if unsafe { SetCommTimeouts(handle, &timeouts) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
@@ -1,38 +1,242 @@
 use tauri::{
     plugin::{Builder, TauriPlugin},
-    Manager, Runtime,
+    Invoke, Manager, RunEvent, Runtime,
 };
 
-use command::{available_ports, cancel_read, close, close_all, force_close, open, read, write, write_binary};
-use state::SerialportState;
+#[cfg(feature = "tokio")]
+pub use async_port::get_async_port;
+use command::{
+    acknowledge_read, acquire_lease, at_command, at_init, available_ports,
+    available_ports_filtered, available_ports_info, batch_open, bytes_to_read, bytes_to_write,
+    cancel_read, check_window_access, clear_input_buffer, close, close_all, close_all_on_exit,
+    close_inactive_ports, create_virtual_port_pair, flush, flush_write_queue, force_close,
+    get_port_settings, get_port_type, get_statistics, is_open, list_open_ports, open,
+    open_bluetooth, open_pipe, open_tcp, open_with_defaults, open_with_reconnect, peek, port_info,
+    read, read_carrier_detect, read_clear_to_send, read_data_set_ready, read_discard,
+    read_ring_indicator, release_lease, reopen, request_port, reset_statistics, scan_baud_rate,
+    send_break, send_xoff, send_xon, set_baud_rate, set_hex_dump, start_port_monitor,
+    stop_port_monitor, supported_baud_rates, transaction, update_settings, windows_friendly_name,
+    write, write_all, write_base64, write_binary, write_binary_all, write_binary_slice,
+    write_binary_with_drain, write_chunks, write_cobs, write_data_terminal_ready, write_hex,
+    write_line, write_modbus_rtu, write_queued, write_repeated, write_request_to_send,
+    write_u16_be, write_u16_le, write_u32_be, write_u32_le, write_u8, write_with_crc,
+    write_with_drain, write_with_encoding,
+};
+pub use error::Error;
+pub use handle::{read_raw, SerialportHandle};
+#[cfg(feature = "testing")]
+pub use mock::MockSerialPort;
+use pool::ThreadPool;
+#[cfg(feature = "pty")]
+pub use pty::create_pty_pair;
+#[cfg(feature = "testing")]
+pub use recorder::load_recording;
+use recorder::Recorder;
+pub use state::SerialportState;
+pub use state::{LogLevel, SerialportPluginConfig};
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex},
+    path::PathBuf,
+    sync::{atomic::AtomicU64, Arc, Mutex},
 };
+#[cfg(feature = "streams")]
+pub use stream::stream_port;
+// first, so that its macros are visible in the modules below
+#[macro_use]
+mod logging;
+#[cfg(feature = "tokio")]
+mod async_port;
+mod bluetooth;
 mod command;
+mod crc;
 mod error;
+mod framing;
+mod handle;
+#[cfg(any(unix, windows))]
+mod inter_char;
+#[cfg(any(test, feature = "testing"))]
+mod mock;
+mod peek;
+#[cfg(windows)]
+mod pipe;
+mod pool;
+#[cfg(feature = "pty")]
+mod pty;
+#[cfg(all(unix, feature = "eventfd"))]
+mod readiness;
+mod recorder;
+#[cfg(windows)]
+mod registry;
 mod state;
+#[cfg(feature = "streams")]
+mod stream;
+#[cfg(target_os = "linux")]
+mod sysfs;
+mod tcp;
+#[cfg(test)]
 mod test;
 
+/// Reject commands naming a `path` the window may not use, see `check_window_access`.
+///
+/// Commands acting on every open port, like `close_all`, only act on the ports the window may use.
+fn with_window_acl<R: Runtime, F: Fn(Invoke<R>) + Send + Sync + 'static>(
+    handler: F,
+) -> impl Fn(Invoke<R>) + Send + Sync + 'static {
+    move |invoke: Invoke<R>| {
+        if let Some(path) = invoke
+            .message
+            .payload()
+            .get("path")
+            .and_then(|path| path.as_str())
+        {
+            let window = invoke.message.window();
+            let state = window.state::<SerialportState>();
+            if let Err(error) = check_window_access(&state.config, window.label(), path) {
+                invoke.resolver.reject(error);
+                return;
+            }
+        }
+        handler(invoke)
+    }
+}
+
 /// Initializes the plugin.
-pub fn init<R: Runtime>() -> TauriPlugin<R> {
+///
+/// `max_worker_threads` caps the threads used to read ports, `None` starts one per reading port.
+/// Once the cap is reached further `read` calls wait until another port stops reading.
+///
+/// Further settings are read from `plugins.serialport` in `tauri.conf.json`, see
+/// `SerialportPluginConfig`.
+///
+/// `record_mode` appends everything read from or written to any port to that JSON Lines file,
+/// which `load_recording` (feature `testing`) replays for offline debugging.
+pub fn init<R: Runtime>(
+    max_worker_threads: Option<usize>,
+    record_mode: Option<PathBuf>,
+) -> TauriPlugin<R> {
     Builder::new("serialport")
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(with_window_acl(tauri::generate_handler![
+            acknowledge_read,
+            acquire_lease,
+            at_command,
+            at_init,
             available_ports,
+            available_ports_filtered,
+            available_ports_info,
+            batch_open,
+            bytes_to_read,
+            bytes_to_write,
             cancel_read,
+            clear_input_buffer,
             close,
             close_all,
+            close_inactive_ports,
+            create_virtual_port_pair,
+            flush,
+            flush_write_queue,
             force_close,
+            get_port_settings,
+            get_port_type,
+            get_statistics,
+            is_open,
+            list_open_ports,
             open,
+            open_bluetooth,
+            open_pipe,
+            open_tcp,
+            open_with_defaults,
+            open_with_reconnect,
+            peek,
+            port_info,
             read,
+            read_carrier_detect,
+            read_clear_to_send,
+            read_data_set_ready,
+            read_discard,
+            read_ring_indicator,
+            release_lease,
+            reopen,
+            request_port,
+            reset_statistics,
+            scan_baud_rate,
+            send_break,
+            send_xoff,
+            send_xon,
+            set_baud_rate,
+            set_hex_dump,
+            start_port_monitor,
+            stop_port_monitor,
+            supported_baud_rates,
+            transaction,
+            update_settings,
+            windows_friendly_name,
             write,
+            write_all,
+            write_base64,
             write_binary,
-        ])
+            write_binary_all,
+            write_binary_slice,
+            write_binary_with_drain,
+            write_chunks,
+            write_cobs,
+            write_data_terminal_ready,
+            write_hex,
+            write_line,
+            write_modbus_rtu,
+            write_queued,
+            write_repeated,
+            write_request_to_send,
+            write_u16_be,
+            write_u16_le,
+            write_u32_be,
+            write_u32_le,
+            write_u8,
+            write_with_crc,
+            write_with_drain,
+            write_with_encoding,
+        ]))
         .setup(move |app_handle| {
+            let config = match app_handle.config().plugins.0.get("serialport") {
+                Some(config) => serde_json::from_value(config.clone())?,
+                None => SerialportPluginConfig::default(),
+            };
+            if let Some(log_level) = config.log_level {
+                logging::set_max_level(log_level.into());
+            }
+            if config
+                .allowed_ports
+                .as_ref()
+                .map_or(true, |allowed_ports| allowed_ports.is_empty())
+            {
+                warn!(
+                    "No allowedPorts configured for the serialport plugin, any port can be opened!"
+                );
+            }
+            let recorder = match record_mode {
+                Some(path) => Some(Arc::new(Recorder::new(&path)?)),
+                None => None,
+            };
             app_handle.manage(SerialportState {
                 serialports: Arc::new(Mutex::new(HashMap::new())),
+                pool: Arc::new(ThreadPool::new(max_worker_threads)),
+                port_monitor: Mutex::new(None),
+                recorder,
+                config,
+                leases: Mutex::new(HashMap::new()),
+                lease_count: AtomicU64::new(0),
+                #[cfg(test)]
+                port_event_hook: None,
+                #[cfg(feature = "tokio")]
+                async_ports: Mutex::new(HashMap::new()),
             });
             Ok(())
         })
+        .on_event(|app_handle, event| {
+            if let RunEvent::Exit = event {
+                if let Some(state) = app_handle.try_state::<SerialportState>() {
+                    close_all_on_exit(&state);
+                }
+            }
+        })
         .build()
 }
@@ -0,0 +1,42 @@
+use log::{Level, LevelFilter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Most verbose level the plugin logs at, see `SerialportPluginConfig::log_level`.
+static MAX_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Trace as usize);
+
+pub(crate) fn set_max_level(level: LevelFilter) {
+    MAX_LEVEL.store(level as usize, Ordering::Relaxed);
+}
+
+pub(crate) fn enabled(level: Level) -> bool {
+    level as usize <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Log through the `log` crate unless `level` is more verbose than the configured `logLevel`.
+macro_rules! plugin_log {
+    ($level:ident, $($arg:tt)+) => {
+        if crate::logging::enabled(log::Level::$level) {
+            log::log!(log::Level::$level, $($arg)+)
+        }
+    };
+}
+
+macro_rules! error {
+    ($($arg:tt)+) => { plugin_log!(Error, $($arg)+) };
+}
+
+macro_rules! warn {
+    ($($arg:tt)+) => { plugin_log!(Warn, $($arg)+) };
+}
+
+macro_rules! info {
+    ($($arg:tt)+) => { plugin_log!(Info, $($arg)+) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)+) => { plugin_log!(Debug, $($arg)+) };
+}
+
+macro_rules! trace {
+    ($($arg:tt)+) => { plugin_log!(Trace, $($arg)+) };
+}
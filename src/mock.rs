@@ -0,0 +1,221 @@
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::{
+    collections::VecDeque,
+    io,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// A serial port without hardware, for testing commands.
+///
+/// Reads return the data the port was created with and time out once it is used up. Writes are
+/// recorded and returned by `written`. Clones share their data like handles of a real port, and
+/// the control signals behave as if a loopback plug was connected: CTS follows RTS, DSR follows
+/// DTR.
+#[derive(Clone)]
+pub struct MockSerialPort {
+    inner: Arc<Mutex<MockState>>,
+}
+
+struct MockState {
+    read_data: VecDeque<u8>,
+    read_errors: VecDeque<io::ErrorKind>,
+    written: Vec<u8>,
+    baud_rate: u32,
+    data_bits: DataBits,
+    flow_control: FlowControl,
+    parity: Parity,
+    stop_bits: StopBits,
+    timeout: Duration,
+    request_to_send: bool,
+    data_terminal_ready: bool,
+}
+
+impl MockSerialPort {
+    /// Create a port at 9600 baud 8N1 that reads `read_data`.
+    pub fn new(read_data: Vec<u8>) -> Self {
+        MockSerialPort {
+            inner: Arc::new(Mutex::new(MockState {
+                read_data: read_data.into(),
+                read_errors: VecDeque::new(),
+                written: vec![],
+                baud_rate: 9600,
+                data_bits: DataBits::Eight,
+                flow_control: FlowControl::None,
+                parity: Parity::None,
+                stop_bits: StopBits::One,
+                timeout: Duration::from_millis(200),
+                request_to_send: false,
+                data_terminal_ready: false,
+            })),
+        }
+    }
+
+    /// Queue more data to be read.
+    pub fn push_read_data(&self, data: &[u8]) {
+        self.state().read_data.extend(data);
+    }
+
+    /// Queue an error of `kind` to be returned by a read before the queued data.
+    pub fn push_read_error(&self, kind: io::ErrorKind) {
+        self.state().read_errors.push_back(kind);
+    }
+
+    /// Everything written to the port so far.
+    pub fn written(&self) -> Vec<u8> {
+        self.state().written.clone()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        // a test that panicked while holding the lock fails anyway
+        match self.inner.lock() {
+            Ok(state) => state,
+            Err(error) => error.into_inner(),
+        }
+    }
+}
+
+impl io::Read for MockSerialPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.state();
+        if let Some(kind) = state.read_errors.pop_front() {
+            return Err(io::Error::new(kind, "Injected read error"));
+        }
+        if state.read_data.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Operation timed out",
+            ));
+        }
+        let size = buf.len().min(state.read_data.len());
+        for (byte, data) in buf.iter_mut().zip(state.read_data.drain(..size)) {
+            *byte = data;
+        }
+        Ok(size)
+    }
+}
+
+impl io::Write for MockSerialPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.state().written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SerialPort for MockSerialPort {
+    fn name(&self) -> Option<String> {
+        None
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(self.state().baud_rate)
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(self.state().data_bits)
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(self.state().flow_control)
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(self.state().parity)
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(self.state().stop_bits)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.state().timeout
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        self.state().baud_rate = baud_rate;
+        Ok(())
+    }
+
+    fn set_data_bits(&mut self, data_bits: DataBits) -> serialport::Result<()> {
+        self.state().data_bits = data_bits;
+        Ok(())
+    }
+
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> serialport::Result<()> {
+        self.state().flow_control = flow_control;
+        Ok(())
+    }
+
+    fn set_parity(&mut self, parity: Parity) -> serialport::Result<()> {
+        self.state().parity = parity;
+        Ok(())
+    }
+
+    fn set_stop_bits(&mut self, stop_bits: StopBits) -> serialport::Result<()> {
+        self.state().stop_bits = stop_bits;
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.state().timeout = timeout;
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> serialport::Result<()> {
+        self.state().request_to_send = level;
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, level: bool) -> serialport::Result<()> {
+        self.state().data_terminal_ready = level;
+        Ok(())
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(self.state().request_to_send)
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(self.state().data_terminal_ready)
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        Ok(self.state().read_data.len() as u32)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        match buffer_to_clear {
+            ClearBuffer::Input | ClearBuffer::All => self.state().read_data.clear(),
+            ClearBuffer::Output => {}
+        }
+        Ok(())
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(self.clone()))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::{
+    io,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// Bytes taken from a port by `peek` but not read yet, shared by every clone of its port.
+pub type PeekBuffer = Arc<Mutex<Vec<u8>>>;
+
+/// Lock `buffer`, which only holds bytes, so a panic while it was locked leaves it usable.
+pub fn lock(buffer: &PeekBuffer) -> MutexGuard<'_, Vec<u8>> {
+    match buffer.lock() {
+        Ok(buffer) => buffer,
+        Err(error) => error.into_inner(),
+    }
+}
+
+/// A port whose reads return the bytes in its `PeekBuffer` before reading the port again.
+///
+/// Serial devices cannot be peeked at by the OS, `MSG_PEEK` only works on sockets, so `peek`
+/// reads the bytes and keeps them here instead.
+pub struct PeekablePort {
+    inner: Box<dyn SerialPort>,
+    buffer: PeekBuffer,
+}
+
+impl PeekablePort {
+    pub fn new(inner: Box<dyn SerialPort>, buffer: PeekBuffer) -> Self {
+        PeekablePort { inner, buffer }
+    }
+}
+
+impl io::Read for PeekablePort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        {
+            let mut peeked = lock(&self.buffer);
+            if !peeked.is_empty() {
+                let size = peeked.len().min(buf.len());
+                buf[..size].copy_from_slice(&peeked[..size]);
+                peeked.drain(..size);
+                return Ok(size);
+            }
+        }
+        self.inner.read(buf)
+    }
+}
+
+impl io::Write for PeekablePort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl SerialPort for PeekablePort {
+    fn name(&self) -> Option<String> {
+        self.inner.name()
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        self.inner.baud_rate()
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        self.inner.data_bits()
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        self.inner.flow_control()
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        self.inner.parity()
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        self.inner.stop_bits()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        self.inner.set_baud_rate(baud_rate)
+    }
+
+    fn set_data_bits(&mut self, data_bits: DataBits) -> serialport::Result<()> {
+        self.inner.set_data_bits(data_bits)
+    }
+
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> serialport::Result<()> {
+        self.inner.set_flow_control(flow_control)
+    }
+
+    fn set_parity(&mut self, parity: Parity) -> serialport::Result<()> {
+        self.inner.set_parity(parity)
+    }
+
+    fn set_stop_bits(&mut self, stop_bits: StopBits) -> serialport::Result<()> {
+        self.inner.set_stop_bits(stop_bits)
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.inner.set_timeout(timeout)
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> serialport::Result<()> {
+        self.inner.write_request_to_send(level)
+    }
+
+    fn write_data_terminal_ready(&mut self, level: bool) -> serialport::Result<()> {
+        self.inner.write_data_terminal_ready(level)
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        self.inner.read_clear_to_send()
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        self.inner.read_data_set_ready()
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        self.inner.read_ring_indicator()
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        self.inner.read_carrier_detect()
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        Ok(self.inner.bytes_to_read()? + lock(&self.buffer).len() as u32)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        self.inner.bytes_to_write()
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        if let ClearBuffer::Input | ClearBuffer::All = buffer_to_clear {
+            lock(&self.buffer).clear();
+        }
+        self.inner.clear(buffer_to_clear)
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(PeekablePort {
+            inner: self.inner.try_clone()?,
+            buffer: self.buffer.clone(),
+        }))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        self.inner.set_break()
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        self.inner.clear_break()
+    }
+}
@@ -0,0 +1,239 @@
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::windows::io::AsRawHandle,
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+use windows_sys::Win32::{Foundation::HANDLE, System::Pipes::PeekNamedPipe};
+
+/// Delay between two checks for data while a read waits for it.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A Windows named pipe used in place of a serial port, e.g. QEMU's `-serial pipe:`.
+///
+/// Like `TcpSerialPort` the bytes are passed through unchanged, the line settings are only
+/// remembered and there are no control signals.
+pub struct PipeSerialPort {
+    name: String,
+    file: File,
+    settings: Arc<Mutex<PipeSettings>>,
+}
+
+struct PipeSettings {
+    baud_rate: u32,
+    data_bits: DataBits,
+    flow_control: FlowControl,
+    parity: Parity,
+    stop_bits: StopBits,
+    timeout: Duration,
+}
+
+impl PipeSerialPort {
+    /// Connect to the pipe at `path`, e.g. `\\.\pipe\com1`, waiting at most `timeout` for each read.
+    pub fn open(path: &str, baud_rate: u32, timeout: Duration) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Ok(PipeSerialPort {
+            name: path.to_string(),
+            file,
+            settings: Arc::new(Mutex::new(PipeSettings {
+                baud_rate,
+                data_bits: DataBits::Eight,
+                flow_control: FlowControl::None,
+                parity: Parity::None,
+                stop_bits: StopBits::One,
+                timeout,
+            })),
+        })
+    }
+
+    fn settings(&self) -> MutexGuard<'_, PipeSettings> {
+        match self.settings.lock() {
+            Ok(settings) => settings,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    /// Number of bytes that can be read without blocking.
+    fn available(&self) -> io::Result<u32> {
+        let mut available = 0;
+        // SAFETY: the handle is open for as long as `self.file` is borrowed, and the pipe is
+        // only asked for its size, so no buffer is passed
+        let result = unsafe {
+            PeekNamedPipe(
+                self.file.as_raw_handle() as HANDLE,
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+                &mut available,
+                std::ptr::null_mut(),
+            )
+        };
+        if result == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(available)
+    }
+}
+
+fn unsupported(operation: &str) -> serialport::Error {
+    serialport::Error::new(
+        serialport::ErrorKind::Io(io::ErrorKind::Unsupported),
+        format!("{} is not supported on named pipes", operation),
+    )
+}
+
+impl Read for PipeSerialPort {
+    /// Wait up to the timeout for data, since reading an empty pipe would block indefinitely.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let deadline = Instant::now() + self.settings().timeout;
+        loop {
+            let available = self.available()? as usize;
+            if available > 0 || buf.is_empty() {
+                let size = available.min(buf.len());
+                return (&self.file).read(&mut buf[..size]);
+            }
+            if Instant::now() >= deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "Operation timed out",
+                ));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+impl Write for PipeSerialPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl SerialPort for PipeSerialPort {
+    fn name(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(self.settings().baud_rate)
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(self.settings().data_bits)
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(self.settings().flow_control)
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(self.settings().parity)
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(self.settings().stop_bits)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.settings().timeout
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        self.settings().baud_rate = baud_rate;
+        Ok(())
+    }
+
+    fn set_data_bits(&mut self, data_bits: DataBits) -> serialport::Result<()> {
+        self.settings().data_bits = data_bits;
+        Ok(())
+    }
+
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> serialport::Result<()> {
+        self.settings().flow_control = flow_control;
+        Ok(())
+    }
+
+    fn set_parity(&mut self, parity: Parity) -> serialport::Result<()> {
+        self.settings().parity = parity;
+        Ok(())
+    }
+
+    fn set_stop_bits(&mut self, stop_bits: StopBits) -> serialport::Result<()> {
+        self.settings().stop_bits = stop_bits;
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.settings().timeout = timeout;
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        Ok(self.available()?)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        if let ClearBuffer::Input | ClearBuffer::All = buffer_to_clear {
+            let mut buf = [0; 4096];
+            loop {
+                let available = (self.available()? as usize).min(buf.len());
+                if available == 0 {
+                    break;
+                }
+                (&self.file).read_exact(&mut buf[..available])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(PipeSerialPort {
+            name: self.name.clone(),
+            file: self.file.try_clone()?,
+            settings: self.settings.clone(),
+        }))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        Err(unsupported("Sending a break"))
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        Err(unsupported("Sending a break"))
+    }
+}
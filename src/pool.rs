@@ -0,0 +1,109 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Worker threads shared by the `read` loops of all ports.
+///
+/// Workers are started on demand and reused once a loop is cancelled. A read loop occupies its
+/// worker until it stops, so with `max_workers` set at most that many ports are read at the same
+/// time and further loops wait until a worker is free.
+pub struct ThreadPool {
+    max_workers: Option<usize>,
+    workers: Arc<Mutex<Workers>>,
+    sender: Mutex<Sender<Job>>,
+    receiver: Arc<Mutex<Receiver<Job>>>,
+}
+
+/// Worker bookkeeping of a `ThreadPool`, matching every queued job to a worker so that a job is
+/// never queued for a worker another job is about to occupy.
+#[derive(Default)]
+struct Workers {
+    /// Workers started so far, they run until the pool is dropped.
+    started: usize,
+    /// Workers waiting for a job that no queued job is meant for yet.
+    idle: usize,
+    /// Jobs queued while no worker was idle and none could be started.
+    waiting: usize,
+}
+
+impl ThreadPool {
+    pub fn new(max_workers: Option<usize>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        ThreadPool {
+            max_workers,
+            workers: Arc::default(),
+            sender: Mutex::new(sender),
+            receiver: Arc::new(Mutex::new(receiver)),
+        }
+    }
+
+    /// Run `job` on a free worker, starting a new one if none is idle and the limit allows it.
+    pub fn execute<F: FnOnce() + Send + 'static>(&self, job: F) {
+        match self.workers.lock() {
+            Ok(mut workers) => {
+                let below_limit = match self.max_workers {
+                    Some(max_workers) => workers.started < max_workers,
+                    None => true,
+                };
+                if workers.idle > 0 {
+                    // claimed now, a second job queued before the worker picks this one up
+                    // starts a worker of its own
+                    workers.idle -= 1;
+                } else if below_limit {
+                    workers.started += 1;
+                    self.spawn_worker();
+                } else {
+                    workers.waiting += 1;
+                }
+            }
+            Err(error) => error!("Failed to queue read job: {}", error),
+        }
+        match self.sender.lock() {
+            Ok(sender) => {
+                // the receiver lives as long as the pool, so sending cannot fail
+                let _ = sender.send(Box::new(job));
+            }
+            Err(error) => error!("Failed to queue read job: {}", error),
+        }
+    }
+
+    /// Start a worker for the job about to be queued.
+    fn spawn_worker(&self) {
+        let workers = self.workers.clone();
+        let receiver = self.receiver.clone();
+        thread::spawn(move || loop {
+            let job = match receiver.lock() {
+                Ok(receiver) => receiver.recv(),
+                Err(_) => break,
+            };
+            match job {
+                Ok(job) => {
+                    // keep the worker alive if a read loop panics
+                    if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                        error!("Read job panicked!");
+                    }
+                }
+                Err(_) => break,
+            }
+            match workers.lock() {
+                // take over a job that waited for a free worker, or wait for the next one
+                Ok(mut workers) if workers.waiting > 0 => workers.waiting -= 1,
+                Ok(mut workers) => workers.idle += 1,
+                Err(_) => break,
+            }
+        });
+    }
+}
+
+impl Default for ThreadPool {
+    fn default() -> Self {
+        ThreadPool::new(None)
+    }
+}
@@ -0,0 +1,54 @@
+use crate::error::Error;
+
+/// Create two connected virtual serial ports and return their device paths.
+///
+/// Data written to one port can be read from the other, so the pair stands in for a device in
+/// tests. Each port is the slave side of a pseudo-terminal, a background thread copies data
+/// between the master sides. The ports stay available until the process exits.
+#[cfg(unix)]
+pub fn create_pty_pair() -> Result<(String, String), Error> {
+    use serialport::{SerialPort, TTYPort};
+    use std::{io, thread};
+
+    let (master_a, slave_a) = TTYPort::pair()?;
+    let (master_b, slave_b) = TTYPort::pair()?;
+    let path_a = slave_a.name().unwrap_or_default();
+    let path_b = slave_b.name().unwrap_or_default();
+    let bridges = [
+        (master_a.try_clone_native()?, master_b.try_clone_native()?),
+        (master_b, master_a),
+    ];
+    // keep one slave handle each open, the master reads fail once no slave is open
+    let mut slaves = Some((slave_a, slave_b));
+    for (mut from, mut to) in bridges {
+        let slaves = slaves.take();
+        thread::spawn(move || {
+            let _slaves = slaves;
+            let mut buf = [0; 1024];
+            loop {
+                match io::Read::read(&mut from, &mut buf) {
+                    Ok(size) => {
+                        if let Err(error) = io::Write::write_all(&mut to, &buf[..size]) {
+                            error!("Failed to forward virtual port data: {}", error);
+                            break;
+                        }
+                    }
+                    Err(error) if error.kind() == io::ErrorKind::TimedOut => {}
+                    Err(error) => {
+                        error!("Failed to read virtual port: {}", error);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+    Ok((path_a, path_b))
+}
+
+/// Create two connected virtual serial ports, only supported on Linux and macOS.
+#[cfg(not(unix))]
+pub fn create_pty_pair() -> Result<(String, String), Error> {
+    Err(Error::Unsupported(
+        "Virtual serial port pairs are not supported on this platform".to_string(),
+    ))
+}
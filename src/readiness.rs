@@ -0,0 +1,26 @@
+use nix::poll::{poll, PollFd, PollFlags};
+use std::os::unix::io::RawFd;
+use std::time::Duration;
+
+/// Waits for a port to become readable, on a duplicate of its file descriptor owned by the
+/// read loop so a port closed meanwhile leaves nothing dangling.
+pub struct Readiness(RawFd);
+
+impl Readiness {
+    pub fn new(raw_fd: RawFd) -> Option<Readiness> {
+        nix::unistd::dup(raw_fd).ok().map(Readiness)
+    }
+
+    /// Wait until data can be read or `timeout` has passed.
+    pub fn wait(&self, timeout: Duration) {
+        let mut fds = [PollFd::new(self.0, PollFlags::POLLIN)];
+        // an error or hangup is left for the following read to report
+        let _ = poll(&mut fds, timeout.as_millis().min(i32::MAX as u128) as i32);
+    }
+}
+
+impl Drop for Readiness {
+    fn drop(&mut self) {
+        let _ = nix::unistd::close(self.0);
+    }
+}
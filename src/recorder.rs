@@ -0,0 +1,276 @@
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Whether recorded data was received from or sent to the port.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Read,
+    Write,
+}
+
+/// One line of a recording.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecordEntry {
+    /// Unix time in milliseconds.
+    pub timestamp_ms: u64,
+    pub port: String,
+    pub direction: Direction,
+    pub data: Vec<u8>,
+}
+
+/// Appends the traffic of every port to a JSON Lines file, one `RecordEntry` per line.
+pub struct Recorder {
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    pub fn new(path: &Path) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|error| {
+                Error::IoError(format!(
+                    "Error opening recording {}: {}",
+                    path.display(),
+                    error
+                ))
+            })?;
+        Ok(Recorder {
+            file: Mutex::new(file),
+        })
+    }
+
+    fn record(&self, port: &str, direction: Direction, data: &[u8]) {
+        let entry = RecordEntry {
+            timestamp_ms: crate::command::timestamp_ms(),
+            port: port.to_string(),
+            direction,
+            data: data.to_vec(),
+        };
+        let mut line = match serde_json::to_vec(&entry) {
+            Ok(line) => line,
+            Err(error) => {
+                error!("Failed to record serial port {}: {}", port, error);
+                return;
+            }
+        };
+        line.push(b'\n');
+        // a poisoned lock only means another write panicked, the file is still usable
+        let mut file = match self.file.lock() {
+            Ok(file) => file,
+            Err(error) => error.into_inner(),
+        };
+        if let Err(error) = file.write_all(&line) {
+            error!("Failed to record serial port {}: {}", port, error);
+        }
+    }
+}
+
+/// A port that passes everything through to `inner` and records the data read and written.
+pub struct RecordingPort {
+    inner: Box<dyn SerialPort>,
+    port: String,
+    recorder: Arc<Recorder>,
+}
+
+impl RecordingPort {
+    pub fn new(inner: Box<dyn SerialPort>, port: &str, recorder: Arc<Recorder>) -> Self {
+        RecordingPort {
+            inner,
+            port: port.to_string(),
+            recorder,
+        }
+    }
+}
+
+impl io::Read for RecordingPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.inner.read(buf)?;
+        if size > 0 {
+            self.recorder
+                .record(&self.port, Direction::Read, &buf[..size]);
+        }
+        Ok(size)
+    }
+}
+
+impl io::Write for RecordingPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let size = self.inner.write(buf)?;
+        if size > 0 {
+            self.recorder
+                .record(&self.port, Direction::Write, &buf[..size]);
+        }
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl SerialPort for RecordingPort {
+    fn name(&self) -> Option<String> {
+        self.inner.name()
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        self.inner.baud_rate()
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        self.inner.data_bits()
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        self.inner.flow_control()
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        self.inner.parity()
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        self.inner.stop_bits()
+    }
+
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        self.inner.set_baud_rate(baud_rate)
+    }
+
+    fn set_data_bits(&mut self, data_bits: DataBits) -> serialport::Result<()> {
+        self.inner.set_data_bits(data_bits)
+    }
+
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> serialport::Result<()> {
+        self.inner.set_flow_control(flow_control)
+    }
+
+    fn set_parity(&mut self, parity: Parity) -> serialport::Result<()> {
+        self.inner.set_parity(parity)
+    }
+
+    fn set_stop_bits(&mut self, stop_bits: StopBits) -> serialport::Result<()> {
+        self.inner.set_stop_bits(stop_bits)
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.inner.set_timeout(timeout)
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> serialport::Result<()> {
+        self.inner.write_request_to_send(level)
+    }
+
+    fn write_data_terminal_ready(&mut self, level: bool) -> serialport::Result<()> {
+        self.inner.write_data_terminal_ready(level)
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        self.inner.read_clear_to_send()
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        self.inner.read_data_set_ready()
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        self.inner.read_ring_indicator()
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        self.inner.read_carrier_detect()
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        self.inner.bytes_to_read()
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        self.inner.bytes_to_write()
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        self.inner.clear(buffer_to_clear)
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(RecordingPort {
+            inner: self.inner.try_clone()?,
+            port: self.port.clone(),
+            recorder: self.recorder.clone(),
+        }))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        self.inner.set_break()
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        self.inner.clear_break()
+    }
+}
+
+/// Load a recording made with `record_mode` for offline debugging.
+///
+/// Returns a state in which every recorded port is open as a `MockSerialPort` that reads back
+/// the data received during the recording, in order but without the original timing. Manage it
+/// in place of the plugin state, e.g. in a `tauri::test` app.
+#[cfg(any(test, feature = "testing"))]
+pub fn load_recording<P: AsRef<Path>>(path: P) -> Result<crate::state::SerialportState, Error> {
+    use crate::{mock::MockSerialPort, state::SerialportState};
+    use std::{
+        collections::HashMap,
+        io::{BufRead, BufReader},
+    };
+
+    let path = path.as_ref();
+    let open_error = |error: io::Error| {
+        Error::IoError(format!(
+            "Error reading recording {}: {}",
+            path.display(),
+            error
+        ))
+    };
+    let file = File::open(path).map_err(open_error)?;
+    let mut ports: HashMap<String, MockSerialPort> = HashMap::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(open_error)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: RecordEntry = serde_json::from_str(&line).map_err(|error| {
+            Error::InvalidArgument(format!(
+                "Invalid entry on line {} of recording {}: {}",
+                index + 1,
+                path.display(),
+                error
+            ))
+        })?;
+        let port = ports
+            .entry(entry.port)
+            .or_insert_with(|| MockSerialPort::new(vec![]));
+        if entry.direction == Direction::Read {
+            port.push_read_data(&entry.data);
+        }
+    }
+    let state = SerialportState::default();
+    for (path, port) in ports {
+        state.insert_port(&path, Box::new(port));
+    }
+    Ok(state)
+}
@@ -0,0 +1,145 @@
+use std::{collections::HashMap, iter, ptr};
+use windows_sys::Win32::{
+    Foundation::{ERROR_NO_MORE_ITEMS, ERROR_SUCCESS},
+    System::Registry::{
+        RegCloseKey, RegEnumKeyExW, RegGetValueW, RegOpenKeyExW, HKEY, HKEY_LOCAL_MACHINE,
+        KEY_READ, RRF_RT_REG_SZ,
+    },
+};
+
+/// Registry key listing every device Plug and Play knows about.
+const ENUM_KEY: &str = r"SYSTEM\CurrentControlSet\Enum";
+
+/// Read the friendly names of the serial ports from the device registry, e.g.
+/// `USB Serial Port (COM3)` for `COM3`.
+///
+/// Devices are stored as `Enum\{enumerator}\{device}\{instance}`, where serial ports have their
+/// COM name in `Device Parameters\PortName`. The port names are upper case.
+pub fn friendly_names() -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    let enum_key = match Key::open(HKEY_LOCAL_MACHINE, &wide(ENUM_KEY)) {
+        Some(enum_key) => enum_key,
+        None => return names,
+    };
+    for enumerator in enum_key.subkeys() {
+        for device in enumerator.subkeys() {
+            for instance in device.subkeys() {
+                let port_name = instance.string(Some("Device Parameters"), "PortName");
+                let friendly_name = instance.string(None, "FriendlyName");
+                if let (Some(port_name), Some(friendly_name)) = (port_name, friendly_name) {
+                    names.insert(port_name.to_uppercase(), friendly_name);
+                }
+            }
+        }
+    }
+    names
+}
+
+/// `value` as a nul terminated UTF-16 string.
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(iter::once(0)).collect()
+}
+
+/// An open registry key, closed when dropped.
+struct Key(HKEY);
+
+impl Key {
+    /// Open `name`, a nul terminated UTF-16 string, below `parent` for reading.
+    fn open(parent: HKEY, name: &[u16]) -> Option<Key> {
+        let mut key = 0;
+        // SAFETY: `name` is nul terminated and `key` outlives the call
+        let result = unsafe { RegOpenKeyExW(parent, name.as_ptr(), 0, KEY_READ, &mut key) };
+        if result == ERROR_SUCCESS {
+            Some(Key(key))
+        } else {
+            None
+        }
+    }
+
+    /// Open every subkey that can be read, skipping the others.
+    fn subkeys(&self) -> Vec<Key> {
+        let mut subkeys = vec![];
+        // key names are at most 255 characters long
+        let mut name = [0u16; 256];
+        for index in 0.. {
+            let mut len = name.len() as u32;
+            // SAFETY: `name` holds `len` characters, the optional outputs are null
+            let result = unsafe {
+                RegEnumKeyExW(
+                    self.0,
+                    index,
+                    name.as_mut_ptr(),
+                    &mut len,
+                    ptr::null(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                )
+            };
+            if result != ERROR_SUCCESS {
+                if result != ERROR_NO_MORE_ITEMS {
+                    warn!(
+                        "Failed to list the registry key of a device: error {}",
+                        result
+                    );
+                }
+                break;
+            }
+            // `len` excludes the nul the name is terminated with
+            if let Some(subkey) = Key::open(self.0, &name[..=len as usize]) {
+                subkeys.push(subkey);
+            }
+        }
+        subkeys
+    }
+
+    /// Read the string `value` of `subkey`, or of this key if `None`.
+    fn string(&self, subkey: Option<&str>, value: &str) -> Option<String> {
+        let subkey_name = subkey.map(wide);
+        let subkey = subkey_name
+            .as_ref()
+            .map_or(ptr::null(), |subkey_name| subkey_name.as_ptr());
+        let value = wide(value);
+        let mut size = 0;
+        // SAFETY: the names are nul terminated, without a buffer only the size is returned
+        let result = unsafe {
+            RegGetValueW(
+                self.0,
+                subkey,
+                value.as_ptr(),
+                RRF_RT_REG_SZ,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut size,
+            )
+        };
+        if result != ERROR_SUCCESS {
+            return None;
+        }
+        let mut data = vec![0u16; (size as usize + 1) / 2];
+        // SAFETY: `data` holds `size` bytes
+        let result = unsafe {
+            RegGetValueW(
+                self.0,
+                subkey,
+                value.as_ptr(),
+                RRF_RT_REG_SZ,
+                ptr::null_mut(),
+                data.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+        if result != ERROR_SUCCESS {
+            return None;
+        }
+        let len = data.iter().position(|&c| c == 0).unwrap_or(data.len());
+        Some(String::from_utf16_lossy(&data[..len]))
+    }
+}
+
+impl Drop for Key {
+    fn drop(&mut self) {
+        // SAFETY: the key was opened by `Key::open` and is not used afterwards
+        unsafe { RegCloseKey(self.0) };
+    }
+}
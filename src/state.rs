@@ -1,28 +1,641 @@
-use serde::Serialize;
-use serialport::{self, SerialPort};
+use crate::peek::PeekBuffer;
+use crate::pool::ThreadPool;
+use crate::recorder::Recorder;
+use serde::{Deserialize, Serialize};
+use serialport::{
+    self, DataBits, FlowControl, Parity, SerialPort, SerialPortInfo, SerialPortType, StopBits,
+};
 use std::{
-    collections::HashMap,
-    sync::{mpsc::Sender, Arc, Mutex},
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{Receiver, Sender},
+        Arc, Condvar, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
+/// Configuration of the plugin, read from `plugins.serialport` in `tauri.conf.json`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialportPluginConfig {
+    /// Paths `open` accepts, any other fails with `PermissionDenied`. Unset or empty allows every
+    /// port. With the `regex` feature the entries are regular expressions matching whole paths.
+    pub allowed_ports: Option<Vec<String>>,
+    /// How many ports can be open at the same time.
+    pub max_open_ports: Option<usize>,
+    /// Baud rate of `open` when none is passed, 9600 if unset.
+    pub default_baud_rate: Option<u32>,
+    /// Buffer size of `read` when none is passed.
+    pub default_read_buffer_size: Option<usize>,
+    /// Port patterns each window, by label, may use, matched like `allowed_ports` or `"*"` for all
+    /// ports. Unset lets every window use every port, otherwise unlisted windows can use none.
+    pub window_port_acl: Option<HashMap<String, Vec<String>>>,
+    /// Most verbose level the plugin logs at through the `log` crate, `trace` if unset.
+    pub log_level: Option<LogLevel>,
+}
+
+/// Level of `SerialportPluginConfig::log_level`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for log::LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => log::LevelFilter::Off,
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+/// Receives the name of a port event and the path of the port, see
+/// `SerialportState::port_event_hook`.
+#[cfg(test)]
+pub(crate) type PortEventHook = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
 #[derive(Default)]
 pub struct SerialportState {
     // plugin state, configuration fields
     pub serialports: Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    /// Workers running the `read` loops.
+    pub pool: Arc<ThreadPool>,
+    /// Stop signal of the thread started by `start_port_monitor`.
+    pub port_monitor: Mutex<Option<Sender<usize>>>,
+    /// Records the traffic of every port opened while set, see `init`.
+    pub recorder: Option<Arc<Recorder>>,
+    /// Settings read from `plugins.serialport` in `tauri.conf.json`.
+    pub config: SerialportPluginConfig,
+    /// Ports reserved for one window by `acquire_lease`, by path.
+    pub leases: Mutex<HashMap<String, Lease>>,
+    /// Number of leases handed out, making their tokens unique.
+    pub lease_count: AtomicU64,
+    /// Called with every `plugin-serialport-port-opened` and `plugin-serialport-port-closed`
+    /// event emitted to the windows, which the listeners of a mock app do not receive in Tauri 1.
+    #[cfg(test)]
+    pub(crate) port_event_hook: Option<PortEventHook>,
+    /// Ports handed over to `get_async_port`.
+    #[cfg(feature = "tokio")]
+    pub async_ports:
+        Mutex<HashMap<String, Arc<tokio_crate::sync::Mutex<tokio_serial::SerialStream>>>>,
 }
 pub struct SerialportInfo {
     pub serialport: Box<dyn SerialPort>,
-    pub sender: Option<Sender<usize>>,
+    /// Stop signal of the read loop, holding at most one pending signal.
+    pub sender: Option<crossbeam_channel::Sender<usize>>,
+    /// Progress of the loop queued by `read`, waited on by `cancel_read`.
+    ///
+    /// The loop sends once when it starts and once when it finishes. A disconnect without the
+    /// second message means it panicked.
+    pub read_task: Option<Receiver<()>>,
+    /// Tells the read loop owning `sender` apart from earlier loops of the port.
+    pub read_generation: u64,
+    /// Arguments of the last `read` that was not cancelled, restarted by `reopen`.
+    pub read_options: Option<ReadOptions>,
+    pub config: SerialportConfig,
+    /// Counters shared with the read loop, so it can update them without locking the state.
+    pub counters: Arc<SerialportCounters>,
+    /// Read events emitted but not acknowledged by `acknowledge_read` yet, see
+    /// `ReadOptions::max_pending_events`.
+    pub pending_events: Arc<AtomicUsize>,
+    /// Unix time in milliseconds at which the port was opened.
+    pub opened_at: u64,
+    /// Type of the port as listed by `available_ports_info` when it was opened, see `get_port_type`.
+    pub port_type: String,
+    /// Time without reads or writes after which the read loop closes the port.
+    pub idle_timeout: Option<Duration>,
+    /// Set by `open_with_reconnect`, reopening the port when the read loop loses it.
+    pub reconnect: Option<ReconnectOptions>,
+    /// Whether the port may be read, written or both.
+    pub access_mode: AccessMode,
+    /// Set by `set_hex_dump`, printing everything read and written as a hex dump.
+    pub hex_dump: Arc<AtomicBool>,
+    /// Whether the `init_sequence` of `open` was written, `false` for ports opened without one.
+    pub initialized: bool,
+    /// Bytes taken from the port by `peek`, returned first by every read of `serialport`.
+    pub peek_buffer: PeekBuffer,
+    /// File descriptor of `serialport`, waited on by the read loop instead of sleeping.
+    #[cfg(all(unix, feature = "eventfd"))]
+    pub raw_fd: Option<std::os::unix::io::RawFd>,
+    /// Created by the first `write_queued`, dropping it stops its writer thread.
+    pub write_queue: Option<WriteQueue>,
+}
+
+impl Drop for SerialportInfo {
+    /// Stop the read thread, if any, whenever the port is removed from the state.
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.take() {
+            // the thread may already have stopped on its own, which is fine
+            let _ = sender.try_send(1);
+        }
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl SerialportState {
+    /// Create a state with a `MockSerialPort` open at `path` that reads `data`.
+    pub fn with_mock(path: &str, data: Vec<u8>) -> Self {
+        SerialportState::with_port(path, Box::new(crate::mock::MockSerialPort::new(data)))
+    }
+
+    /// Create a state with `serialport` open at `path`, e.g. a `MockSerialPort` kept by the test.
+    pub fn with_port(path: &str, serialport: Box<dyn SerialPort>) -> Self {
+        let state = SerialportState::default();
+        state.insert_port(path, serialport);
+        state
+    }
+
+    /// Add `serialport` as open at `path`, replacing any port open there.
+    pub fn insert_port(&self, path: &str, serialport: Box<dyn SerialPort>) {
+        let config = SerialportConfig::new(
+            serialport.baud_rate().unwrap_or(9600),
+            serialport.data_bits().unwrap_or(DataBits::Eight),
+            serialport.flow_control().unwrap_or(FlowControl::None),
+            serialport.parity().unwrap_or(Parity::None),
+            serialport.stop_bits().unwrap_or(StopBits::One),
+            serialport.timeout(),
+        );
+        let peek_buffer = PeekBuffer::default();
+        if let Ok(mut serialports) = self.serialports.lock() {
+            serialports.insert(
+                path.to_string(),
+                SerialportInfo {
+                    serialport: Box::new(crate::peek::PeekablePort::new(
+                        serialport,
+                        peek_buffer.clone(),
+                    )),
+                    peek_buffer,
+                    sender: None,
+                    read_task: None,
+                    read_generation: 0,
+                    read_options: None,
+                    config,
+                    counters: Arc::new(SerialportCounters::default()),
+                    pending_events: Arc::new(AtomicUsize::new(0)),
+                    opened_at: crate::command::timestamp_ms(),
+                    port_type: "Unknown".to_string(),
+                    idle_timeout: None,
+                    reconnect: None,
+                    access_mode: AccessMode::ReadWrite,
+                    hex_dump: Arc::new(AtomicBool::new(false)),
+                    initialized: false,
+                    #[cfg(all(unix, feature = "eventfd"))]
+                    raw_fd: None,
+                    write_queue: None,
+                },
+            );
+        }
+    }
+}
+
+/// What a port was opened for by `open`.
+///
+/// The OS opens serial ports for reading and writing alike, the mode is enforced by the plugin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessMode {
+    ReadOnly,
+    WriteOnly,
+    ReadWrite,
+}
+
+impl AccessMode {
+    pub fn can_read(self) -> bool {
+        self != AccessMode::WriteOnly
+    }
+
+    pub fn can_write(self) -> bool {
+        self != AccessMode::ReadOnly
+    }
+}
+
+/// Data submitted by `write_queued`, written in order by a thread of its own.
+pub struct WriteQueue {
+    pub writes: Arc<Mutex<QueuedWrites>>,
+    /// Notified by the writer thread once `writes` has no pending data left.
+    pub drained: Arc<Condvar>,
+    /// Wakes the writer thread up after a write was queued.
+    pub sender: Sender<()>,
+}
+
+#[derive(Default)]
+pub struct QueuedWrites {
+    /// Data waiting to be written, each with the delay to wait between its bytes. The write in
+    /// progress stays at the front until it is done.
+    pub pending: VecDeque<(Vec<u8>, Option<Duration>)>,
+    /// First write that failed since the last `flush_write_queue`.
+    pub error: Option<String>,
+}
+
+/// The arguments a `read` loop was started with.
+#[derive(Clone, Debug, Default)]
+pub struct ReadOptions {
+    /// Longest pause in milliseconds between two reads, which is shortened while data arrives.
+    pub timeout: Option<u64>,
+    pub size: Option<usize>,
+    pub read_encoding: Option<String>,
+    pub delimiter: Option<Vec<u8>>,
+    pub packet_size: Option<usize>,
+    pub max_read_errors: Option<u32>,
+    /// Event the data is emitted under instead of `plugin-serialport-read-{path}`.
+    pub event_name: Option<String>,
+    /// Label of the window the events are emitted to instead of the calling one.
+    pub target_window: Option<String>,
+    /// Protocol the messages are decoded with, see `get_framer`.
+    pub framing: Option<String>,
+    /// Size in bytes of the length header of `"length_prefix"` framing.
+    pub length_field_size: Option<u8>,
+    /// Byte order of the length header of `"length_prefix"` framing.
+    pub endian: Option<String>,
+    /// Checksum trailing every frame, stripped before the frame is emitted.
+    pub crc: Option<String>,
+    /// Time in milliseconds during which received data is collected into a single event.
+    pub batch_ms: Option<u64>,
+    /// Number of unacknowledged read events above which received data is dropped.
+    pub max_pending_events: Option<usize>,
+    /// Time in milliseconds without received bytes after which a silence event is emitted.
+    pub silence_timeout_ms: Option<u64>,
+    /// Emit every delimited frame of a read as its own event instead of all of them together.
+    pub emit_per_packet: Option<bool>,
+}
+
+/// Arguments of `open` for one port of `batch_open`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenParams {
+    pub path: String,
+    pub baud_rate: Option<u32>,
+    pub data_bits: Option<usize>,
+    pub flow_control: Option<String>,
+    pub parity: Option<String>,
+    pub stop_bits: Option<usize>,
+    pub timeout: Option<u64>,
+    pub idle_timeout_ms: Option<u64>,
+    pub access_mode: Option<String>,
+    pub validate_baud_rate: Option<bool>,
+    pub inter_char_timeout_ms: Option<u64>,
+    pub init_sequence: Option<Vec<u8>>,
+    pub init_delay_ms: Option<u64>,
+}
+
+/// Retry parameters of a port opened with `open_with_reconnect`.
+#[derive(Clone)]
+pub struct ReconnectOptions {
+    pub max_retries: u32,
+    /// Delay before the first attempt, doubled after every failed one.
+    pub base_delay_ms: u64,
+    /// The recorder the reopened port is wrapped with, see `SerialportState::recorder`.
+    pub recorder: Option<Arc<Recorder>>,
+}
+
+/// Traffic counters of an open port, see `SerialportStats`.
+#[derive(Default)]
+pub struct SerialportCounters {
+    pub bytes_read: AtomicU64,
+    pub read_count: AtomicU64,
+    pub bytes_written: AtomicU64,
+    pub write_count: AtomicU64,
+    pub read_errors: AtomicU64,
+    /// Bytes dropped because too many read events were pending.
+    pub dropped_bytes: AtomicU64,
+    /// Unix time in milliseconds of the last read or write, `0` before the first one.
+    pub last_activity: AtomicU64,
+}
+
+impl SerialportCounters {
+    pub fn record_read(&self, size: usize) {
+        self.bytes_read.fetch_add(size as u64, Ordering::Relaxed);
+        self.read_count.fetch_add(1, Ordering::Relaxed);
+        self.last_activity
+            .store(crate::command::timestamp_ms(), Ordering::Relaxed);
+    }
+
+    pub fn record_write(&self, size: usize) {
+        self.bytes_written.fetch_add(size as u64, Ordering::Relaxed);
+        self.write_count.fetch_add(1, Ordering::Relaxed);
+        self.last_activity
+            .store(crate::command::timestamp_ms(), Ordering::Relaxed);
+    }
+
+    pub fn record_read_error(&self) {
+        self.read_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_dropped(&self, size: usize) {
+        self.dropped_bytes.fetch_add(size as u64, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        for counter in [
+            &self.bytes_read,
+            &self.read_count,
+            &self.bytes_written,
+            &self.write_count,
+            &self.read_errors,
+            &self.dropped_bytes,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Read and write statistics of an open port, returned by `get_statistics`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialportStats {
+    pub bytes_read: u64,
+    /// Number of reads that returned data.
+    pub read_count: u64,
+    pub bytes_written: u64,
+    /// Number of write calls accepted by the port.
+    pub write_count: u64,
+    /// Number of failed reads, timeouts and interrupted reads excluded.
+    pub read_errors: u64,
+    /// Bytes dropped because the frontend did not keep up with the read events.
+    pub dropped_bytes: u64,
+    /// Unix time in milliseconds at which the port was opened.
+    pub opened_at: u64,
+}
+
+impl SerialportStats {
+    pub fn new(counters: &SerialportCounters, opened_at: u64) -> Self {
+        SerialportStats {
+            bytes_read: counters.bytes_read.load(Ordering::Relaxed),
+            read_count: counters.read_count.load(Ordering::Relaxed),
+            bytes_written: counters.bytes_written.load(Ordering::Relaxed),
+            write_count: counters.write_count.load(Ordering::Relaxed),
+            read_errors: counters.read_errors.load(Ordering::Relaxed),
+            dropped_bytes: counters.dropped_bytes.load(Ordering::Relaxed),
+            opened_at,
+        }
+    }
+}
+
+/// The settings a serial port was opened with.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialportConfig {
+    pub baud_rate: u32,
+    pub data_bits: u8,
+    /// One of `"None"`, `"Odd"` or `"Even"`.
+    pub parity: String,
+    /// One of `"None"`, `"Software"` or `"Hardware"`.
+    pub flow_control: String,
+    pub stop_bits: u8,
+    pub timeout_ms: u64,
+    /// Pause after which a read returns the bytes received so far, see `open`.
+    pub inter_char_timeout_ms: Option<u64>,
+}
+
+impl SerialportConfig {
+    pub fn new(
+        baud_rate: u32,
+        data_bits: DataBits,
+        flow_control: FlowControl,
+        parity: Parity,
+        stop_bits: StopBits,
+        timeout: Duration,
+    ) -> Self {
+        SerialportConfig {
+            baud_rate,
+            data_bits: match data_bits {
+                DataBits::Five => 5,
+                DataBits::Six => 6,
+                DataBits::Seven => 7,
+                DataBits::Eight => 8,
+            },
+            parity: match parity {
+                Parity::None => "None",
+                Parity::Odd => "Odd",
+                Parity::Even => "Even",
+            }
+            .to_string(),
+            flow_control: match flow_control {
+                FlowControl::None => "None",
+                FlowControl::Software => "Software",
+                FlowControl::Hardware => "Hardware",
+            }
+            .to_string(),
+            stop_bits: match stop_bits {
+                StopBits::One => 1,
+                StopBits::Two => 2,
+            },
+            timeout_ms: timeout.as_millis() as u64,
+            inter_char_timeout_ms: None,
+        }
+    }
+}
+
+/// Levels of the input control signals of an open port.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalState {
+    pub clear_to_send: bool,
+    pub data_set_ready: bool,
+    pub ring_indicator: bool,
+    pub carrier_detect: bool,
+}
+
+/// Everything known about an open port, returned by `port_info`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PortInfo {
+    pub config: SerialportConfig,
+    pub stats: SerialportStats,
+    pub signals: SignalState,
+    /// Whether a read loop is running.
+    pub is_reading: bool,
+    /// Whether the `init_sequence` of `open` was written.
+    pub initialized: bool,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct InvokeResult {
     pub code: i32,
     pub message: String,
 }
 
-#[derive(Serialize, Clone)]
-pub struct ReadData<'a> {
-    pub data: &'a [u8],
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadData {
+    pub data: ReadPayload,
     pub size: usize,
+    /// Unix time in milliseconds at which the data was read from the port.
+    pub timestamp_ms: u64,
+}
+
+/// Payload of the read event of a `read` with `"nmea"` framing, one per sentence.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct NmeaData {
+    /// The sentence without line ending, e.g. `$GPGGA,...*47`.
+    pub sentence: String,
+    /// Talker and sentence formatter following the `$`, e.g. `GPGGA`.
+    pub sentence_type: String,
+    /// Unix time in milliseconds at which the sentence was read from the port.
+    pub timestamp_ms: u64,
+}
+
+/// The bytes carried by a `ReadData` event, as a byte array or as an encoded string.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ReadPayload {
+    Raw(Vec<u8>),
+    Encoded(String),
+}
+
+/// How the `read` loop serializes received bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadEncoding {
+    Raw,
+    Hex,
+    Base64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialPortDetail {
+    pub port_name: String,
+    /// One of `"USB"`, `"PCI"`, `"Bluetooth"` or `"Unknown"`.
+    pub port_type: String,
+    pub usb_vid: Option<u16>,
+    pub usb_pid: Option<u16>,
+    pub usb_manufacturer: Option<String>,
+    pub usb_serial_number: Option<String>,
+    pub usb_product: Option<String>,
+    /// Sysfs path of the USB device, which tells identical adapters apart. Linux only.
+    pub device_path: Option<String>,
+    /// Name shown by Windows, e.g. `USB Serial Port (COM3)`. Windows only.
+    pub friendly_name: Option<String>,
+}
+
+impl From<SerialPortInfo> for SerialPortDetail {
+    fn from(info: SerialPortInfo) -> Self {
+        let mut detail = SerialPortDetail {
+            port_name: info.port_name,
+            port_type: String::new(),
+            usb_vid: None,
+            usb_pid: None,
+            usb_manufacturer: None,
+            usb_serial_number: None,
+            usb_product: None,
+            device_path: None,
+            friendly_name: None,
+        };
+        detail.port_type = match info.port_type {
+            SerialPortType::UsbPort(usb) => {
+                detail.usb_vid = Some(usb.vid);
+                detail.usb_pid = Some(usb.pid);
+                detail.usb_manufacturer = usb.manufacturer;
+                detail.usb_serial_number = usb.serial_number;
+                detail.usb_product = usb.product;
+                "USB"
+            }
+            SerialPortType::PciPort => "PCI",
+            SerialPortType::BluetoothPort => "Bluetooth",
+            SerialPortType::Unknown => "Unknown",
+        }
+        .to_string();
+        detail
+    }
+}
+
+/// Narrows the ports offered by `request_port`, like the filters of the Web Serial API.
+///
+/// A port matches if it has every field that is set.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PortFilter {
+    pub usb_vendor_id: Option<u16>,
+    pub usb_product_id: Option<u16>,
+    /// Part of the port name, e.g. `"ttyUSB"`.
+    pub name_pattern: Option<String>,
+}
+
+impl PortFilter {
+    pub fn matches(&self, port: &SerialPortDetail) -> bool {
+        self.usb_vendor_id
+            .map_or(true, |vid| port.usb_vid == Some(vid))
+            && self
+                .usb_product_id
+                .map_or(true, |pid| port.usb_pid == Some(pid))
+            && self
+                .name_pattern
+                .as_ref()
+                .map_or(true, |pattern| port.port_name.contains(pattern.as_str()))
+    }
+}
+
+/// Payload of the `plugin-serialport-port-added` and `plugin-serialport-port-removed` events.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PortEvent {
+    pub port_name: String,
+}
+
+/// Payload of the `plugin-serialport-reconnecting-{path}` event.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconnectData {
+    pub port_name: String,
+    /// Number of the attempt about to be made, starting at 1.
+    pub attempt: u32,
+    /// Time waited before the attempt.
+    pub delay_ms: u64,
+}
+
+/// Payload of the `plugin-serialport-silence-{path}` event.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SilenceData {
+    /// Time since the last byte was received, or since the read started.
+    pub duration_ms: u64,
+}
+
+/// A port reserved by `acquire_lease`, only its window may read or write it until it expires.
+pub struct Lease {
+    pub token: String,
+    pub window_label: String,
+    pub expires_at: Instant,
+}
+
+/// Returned by `acquire_lease` and passed to `release_lease`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaseToken {
+    pub path: String,
+    pub token: String,
+    pub window_label: String,
+    /// Unix time in milliseconds at which the lease expires unless acquired again.
+    pub expires_at_ms: u64,
+}
+
+/// Payload of the `plugin-serialport-overflow-{path}` event emitted when the read loop starts
+/// dropping data.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OverflowData {
+    pub port_name: String,
+    /// Bytes dropped since the port was opened or its statistics were reset.
+    pub dropped_bytes: u64,
+}
+
+/// Payload of the `plugin-serialport-error-{path}` event emitted when the read loop fails.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorData {
+    pub message: String,
+    /// The `std::io::ErrorKind` of the failure, e.g. `"BrokenPipe"`.
+    pub kind: String,
 }
@@ -0,0 +1,61 @@
+use crate::command::{start_read, ReadSink};
+use crate::error::Error;
+use crate::state::{ReadOptions, SerialportState};
+use bytes::Bytes;
+use futures_channel::mpsc::{self, UnboundedSender};
+use futures_core::Stream;
+use std::io;
+
+/// Passes reads on to the stream returned by `stream_port`.
+struct StreamSink {
+    sender: UnboundedSender<Result<Bytes, Error>>,
+}
+
+impl ReadSink for StreamSink {
+    fn data(&mut self, data: &[u8], _timestamp_ms: u64) -> bool {
+        // fails once the stream has been dropped, which stops the loop
+        self.sender
+            .unbounded_send(Ok(Bytes::copy_from_slice(data)))
+            .is_ok()
+    }
+
+    fn error(&mut self, error: &io::Error) {
+        let _ = self
+            .sender
+            .unbounded_send(Err(Error::IoError(error.to_string())));
+    }
+}
+
+/// Read the open port at `path` as a stream of byte chunks, for Rust code consuming it directly.
+///
+/// Runs the same read loop as the `read` command with its default options, so the port cannot
+/// be read with events at the same time. The stream ends when the port is closed, its read is
+/// cancelled or it fails repeatedly; dropping the stream stops the loop.
+pub fn stream_port(
+    state: &SerialportState,
+    path: &str,
+) -> Result<impl Stream<Item = Result<Bytes, Error>>, Error> {
+    let (sender, receiver) = mpsc::unbounded();
+    let mut serialports = state.serialports.lock().map_err(|_| Error::LockPoisoned)?;
+    let serialport_info = match serialports.get_mut(path) {
+        Some(serialport_info) => serialport_info,
+        None => return Err(Error::PortNotFound(path.to_string())),
+    };
+    let started = start_read(
+        serialport_info,
+        StreamSink { sender },
+        &state.pool,
+        state.serialports.clone(),
+        path.to_string(),
+        ReadOptions::default(),
+    )?;
+    if !started {
+        return Err(Error::InvalidArgument(format!(
+            "Serial port {} is already being read",
+            path
+        )));
+    }
+    // the stream cannot be restarted by `reopen`
+    serialport_info.read_options = None;
+    Ok(receiver)
+}
@@ -0,0 +1,77 @@
+use crate::state::SerialPortDetail;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Read the USB metadata of the serial ports listed in `tty_class`, usually `/sys/class/tty`.
+///
+/// Ports that are not backed by a USB device, such as the `ttyS` UART stubs, are left out.
+/// Missing or unreadable files are skipped, a port is reported with whatever could be read.
+pub fn usb_ports(tty_class: &Path) -> Vec<SerialPortDetail> {
+    let entries = match fs::read_dir(tty_class) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+    let mut ports = vec![];
+    for entry in entries.flatten() {
+        let device = match fs::canonicalize(entry.path().join("device")) {
+            Ok(device) => device,
+            Err(_) => continue,
+        };
+        let usb_device = match find_usb_device(&device) {
+            Some(usb_device) => usb_device,
+            None => continue,
+        };
+        let read = |name: &str| {
+            fs::read_to_string(usb_device.join(name))
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let read_id = |name: &str| read(name).and_then(|id| u16::from_str_radix(&id, 16).ok());
+        ports.push(SerialPortDetail {
+            port_name: format!("/dev/{}", entry.file_name().to_string_lossy()),
+            port_type: "USB".to_string(),
+            usb_vid: read_id("idVendor"),
+            usb_pid: read_id("idProduct"),
+            usb_manufacturer: read("manufacturer"),
+            usb_serial_number: read("serial"),
+            usb_product: read("product"),
+            device_path: Some(usb_device.to_string_lossy().into_owned()),
+            friendly_name: None,
+        });
+    }
+    ports
+}
+
+/// Find the USB device among `device` and its parents, which is the interface's parent for
+/// `ttyACM` ports and the grandparent for `ttyUSB` ports.
+fn find_usb_device(device: &Path) -> Option<PathBuf> {
+    device
+        .ancestors()
+        .take(4)
+        .find(|dir| dir.join("idVendor").is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Fill the USB metadata the `serialport` crate left out of `ports` from `usb_ports`, and add
+/// the USB ports it did not enumerate at all.
+pub fn merge_usb_ports(ports: &mut Vec<SerialPortDetail>, usb_ports: Vec<SerialPortDetail>) {
+    for usb_port in usb_ports {
+        match ports
+            .iter_mut()
+            .find(|port| port.port_name == usb_port.port_name)
+        {
+            Some(port) => {
+                port.port_type = usb_port.port_type;
+                port.usb_vid = port.usb_vid.or(usb_port.usb_vid);
+                port.usb_pid = port.usb_pid.or(usb_port.usb_pid);
+                port.usb_manufacturer = port.usb_manufacturer.take().or(usb_port.usb_manufacturer);
+                port.usb_serial_number =
+                    port.usb_serial_number.take().or(usb_port.usb_serial_number);
+                port.usb_product = port.usb_product.take().or(usb_port.usb_product);
+                port.device_path = usb_port.device_path;
+            }
+            None => ports.push(usb_port),
+        }
+    }
+}
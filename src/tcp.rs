@@ -0,0 +1,238 @@
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+/// A serial port reached over a raw TCP connection, e.g. a serial device server.
+///
+/// The bytes are passed through unchanged. The line settings are only remembered, since a raw
+/// connection cannot negotiate them with the server, and there are no control signals: setting
+/// them does nothing and reading them returns `false`.
+pub struct TcpSerialPort {
+    name: String,
+    stream: TcpStream,
+    settings: Arc<Mutex<TcpSettings>>,
+}
+
+struct TcpSettings {
+    baud_rate: u32,
+    data_bits: DataBits,
+    flow_control: FlowControl,
+    parity: Parity,
+    stop_bits: StopBits,
+    timeout: Duration,
+}
+
+impl TcpSerialPort {
+    /// Connect to `host` at `port`, waiting at most `timeout` for each read.
+    pub fn connect(host: &str, port: u16, baud_rate: u32, timeout: Duration) -> io::Result<Self> {
+        let stream = TcpStream::connect((host, port))?;
+        stream.set_nodelay(true)?;
+        Self::from_stream(
+            format!("tcp://{}:{}", host, port),
+            stream,
+            baud_rate,
+            timeout,
+        )
+    }
+
+    /// Use an already connected stream socket named `name`, waiting at most `timeout` for each
+    /// read.
+    pub fn from_stream(
+        name: String,
+        stream: TcpStream,
+        baud_rate: u32,
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        stream.set_read_timeout(Some(timeout))?;
+        Ok(TcpSerialPort {
+            name,
+            stream,
+            settings: Arc::new(Mutex::new(TcpSettings {
+                baud_rate,
+                data_bits: DataBits::Eight,
+                flow_control: FlowControl::None,
+                parity: Parity::None,
+                stop_bits: StopBits::One,
+                timeout,
+            })),
+        })
+    }
+
+    fn settings(&self) -> MutexGuard<'_, TcpSettings> {
+        match self.settings.lock() {
+            Ok(settings) => settings,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    /// Read what already arrived into `buf` without waiting.
+    fn read_available(&self, buf: &mut [u8], peek: bool) -> io::Result<usize> {
+        self.stream.set_nonblocking(true)?;
+        let result = if peek {
+            self.stream.peek(buf)
+        } else {
+            (&self.stream).read(buf)
+        };
+        self.stream.set_nonblocking(false)?;
+        match result {
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(0),
+            result => result,
+        }
+    }
+}
+
+fn unsupported(operation: &str) -> serialport::Error {
+    serialport::Error::new(
+        serialport::ErrorKind::Io(io::ErrorKind::Unsupported),
+        format!("{} is not supported over a socket", operation),
+    )
+}
+
+impl Read for TcpSerialPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.stream.read(buf) {
+            // the read loop tells timeouts apart by kind, which sockets report as `WouldBlock`
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Operation timed out",
+            )),
+            Ok(0) if !buf.is_empty() => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "Connection closed by the server",
+            )),
+            result => result,
+        }
+    }
+}
+
+impl Write for TcpSerialPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl SerialPort for TcpSerialPort {
+    fn name(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(self.settings().baud_rate)
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(self.settings().data_bits)
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(self.settings().flow_control)
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(self.settings().parity)
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(self.settings().stop_bits)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.settings().timeout
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        self.settings().baud_rate = baud_rate;
+        Ok(())
+    }
+
+    fn set_data_bits(&mut self, data_bits: DataBits) -> serialport::Result<()> {
+        self.settings().data_bits = data_bits;
+        Ok(())
+    }
+
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> serialport::Result<()> {
+        self.settings().flow_control = flow_control;
+        Ok(())
+    }
+
+    fn set_parity(&mut self, parity: Parity) -> serialport::Result<()> {
+        self.settings().parity = parity;
+        Ok(())
+    }
+
+    fn set_stop_bits(&mut self, stop_bits: StopBits) -> serialport::Result<()> {
+        self.settings().stop_bits = stop_bits;
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.stream.set_read_timeout(Some(timeout))?;
+        self.settings().timeout = timeout;
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        let mut buf = [0; 4096];
+        Ok(self.read_available(&mut buf, true)? as u32)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        if let ClearBuffer::Input | ClearBuffer::All = buffer_to_clear {
+            let mut buf = [0; 4096];
+            while self.read_available(&mut buf, false)? > 0 {}
+        }
+        Ok(())
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(TcpSerialPort {
+            name: self.name.clone(),
+            stream: self.stream.try_clone()?,
+            settings: self.settings.clone(),
+        }))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        Err(unsupported("Sending a break"))
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        Err(unsupported("Sending a break"))
+    }
+}
@@ -1 +1,2034 @@
+use crate::command::{
+    check_allowed_port, check_window_access, encode_text, format_hex_dump, get_access_mode,
+    get_flow_control, get_parity, get_stop_bits, windows_friendly_name,
+};
+use crate::crc::Crc;
+use crate::framing::{cobs_decode, cobs_encode, Framer};
+use crate::state::{
+    AccessMode, LogLevel, PortFilter, ReadData, ReadPayload, SerialPortDetail, SerialportConfig,
+    SerialportPluginConfig,
+};
+use serialport::{DataBits, FlowControl, Parity, StopBits};
+use std::time::Duration;
 
+#[test]
+fn stop_bits_default_to_one() {
+    assert_eq!(get_stop_bits(None), StopBits::One);
+    assert_eq!(get_stop_bits(Some(3)), StopBits::One);
+    assert_eq!(get_stop_bits(Some(2)), StopBits::Two);
+}
+
+#[test]
+fn parity_and_flow_control_ignore_case() {
+    for value in ["odd", "Odd", "ODD"] {
+        assert_eq!(get_parity(Some(value.to_string())).unwrap(), Parity::Odd);
+    }
+    assert_eq!(
+        get_flow_control(Some("hardware".to_string())).unwrap(),
+        FlowControl::Hardware
+    );
+    assert!(get_parity(Some("mark".to_string())).is_err());
+    assert!(get_flow_control(Some("xon".to_string())).is_err());
+}
+
+#[test]
+fn allowed_ports_allow_everything_unless_set() {
+    let mut config = SerialportPluginConfig::default();
+    assert!(check_allowed_port(&config, "/dev/ttyUSB0").is_ok());
+    config.allowed_ports = Some(Vec::new());
+    assert!(check_allowed_port(&config, "/dev/ttyUSB0").is_ok());
+    config.allowed_ports = Some(vec!["/dev/ttyUSB0".to_string()]);
+    assert!(check_allowed_port(&config, "/dev/ttyUSB0").is_ok());
+    assert!(matches!(
+        check_allowed_port(&config, "/dev/ttyUSB1"),
+        Err(crate::Error::PermissionDenied(_))
+    ));
+    #[cfg(feature = "regex")]
+    {
+        config.allowed_ports = Some(vec!["/dev/ttyUSB[0-9]+".to_string()]);
+        assert!(check_allowed_port(&config, "/dev/ttyUSB12").is_ok());
+        assert!(check_allowed_port(&config, "/dev/ttyUSB0x").is_err());
+    }
+}
+
+#[test]
+fn window_port_acl_limits_listed_windows() {
+    let mut config = SerialportPluginConfig::default();
+    assert!(check_window_access(&config, "untrusted", "/dev/ttyUSB0").is_ok());
+    let mut window_port_acl = std::collections::HashMap::new();
+    window_port_acl.insert("main".to_string(), vec!["*".to_string()]);
+    window_port_acl.insert("plotter".to_string(), vec!["/dev/ttyUSB0".to_string()]);
+    config.window_port_acl = Some(window_port_acl);
+    assert!(check_window_access(&config, "main", "/dev/ttyACM3").is_ok());
+    assert!(check_window_access(&config, "plotter", "/dev/ttyUSB0").is_ok());
+    assert!(matches!(
+        check_window_access(&config, "plotter", "/dev/ttyACM3"),
+        Err(crate::Error::PermissionDenied(_))
+    ));
+    assert!(check_window_access(&config, "untrusted", "/dev/ttyUSB0").is_err());
+}
+
+#[test]
+fn log_level_is_read_from_the_config() {
+    let config: SerialportPluginConfig =
+        serde_json::from_value(serde_json::json!({ "logLevel": "warn" })).unwrap();
+    assert_eq!(config.log_level, Some(LogLevel::Warn));
+    assert_eq!(log::LevelFilter::from(LogLevel::Off), log::LevelFilter::Off);
+}
+
+#[test]
+fn port_filters_match_every_field_set() {
+    let port = SerialPortDetail {
+        port_name: "/dev/ttyUSB0".to_string(),
+        port_type: "USB".to_string(),
+        usb_vid: Some(0x0403),
+        usb_pid: Some(0x6001),
+        usb_manufacturer: None,
+        usb_serial_number: None,
+        usb_product: None,
+        device_path: None,
+        friendly_name: None,
+    };
+    assert!(PortFilter::default().matches(&port));
+    let filter = PortFilter {
+        usb_vendor_id: Some(0x0403),
+        name_pattern: Some("ttyUSB".to_string()),
+        ..Default::default()
+    };
+    assert!(filter.matches(&port));
+    let filter = PortFilter {
+        usb_product_id: Some(0x6015),
+        ..filter
+    };
+    assert!(!filter.matches(&port));
+}
+
+#[cfg(not(windows))]
+#[test]
+fn friendly_names_are_windows_only() {
+    assert!(matches!(
+        windows_friendly_name("COM3".to_string()),
+        Err(crate::error::Error::Unsupported(_))
+    ));
+}
+
+#[test]
+fn access_mode_defaults_to_read_write() {
+    assert_eq!(get_access_mode(None).unwrap(), AccessMode::ReadWrite);
+    assert_eq!(
+        get_access_mode(Some("write_only".to_string())).unwrap(),
+        AccessMode::WriteOnly
+    );
+    assert!(get_access_mode(Some("append".to_string())).is_err());
+}
+
+#[test]
+fn port_settings_are_stored_as_open_arguments() {
+    let config = SerialportConfig::new(
+        9600,
+        DataBits::Seven,
+        FlowControl::Hardware,
+        Parity::Even,
+        StopBits::Two,
+        Duration::from_millis(1500),
+    );
+    assert_eq!(config.baud_rate, 9600);
+    assert_eq!(config.data_bits, 7);
+    assert_eq!(config.flow_control, "Hardware");
+    assert_eq!(config.parity, "Even");
+    assert_eq!(config.stop_bits, 2);
+    assert_eq!(config.timeout_ms, 1500);
+    // the stored strings are accepted again when reopening with them
+    assert_eq!(
+        get_flow_control(Some(config.flow_control)).unwrap(),
+        FlowControl::Hardware
+    );
+    assert_eq!(get_parity(Some(config.parity)).unwrap(), Parity::Even);
+}
+
+#[test]
+fn ipc_types_are_camel_case() {
+    let data = ReadData {
+        data: ReadPayload::Raw(vec![1, 2]),
+        size: 2,
+        timestamp_ms: 5,
+    };
+    let json = serde_json::to_value(&data).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "data": [1, 2], "size": 2, "timestampMs": 5 })
+    );
+    let data: ReadData = serde_json::from_value(json).unwrap();
+    assert_eq!(data.data, ReadPayload::Raw(vec![1, 2]));
+    let config = SerialportConfig::new(
+        9600,
+        DataBits::Eight,
+        FlowControl::None,
+        Parity::None,
+        StopBits::One,
+        Duration::from_millis(200),
+    );
+    let json = serde_json::to_value(&config).unwrap();
+    assert_eq!(json["baudRate"], 9600);
+    assert_eq!(json["timeoutMs"], 200);
+}
+
+#[test]
+fn recording_replays_received_data() {
+    use crate::mock::MockSerialPort;
+    use crate::recorder::{load_recording, Recorder, RecordingPort};
+    use std::io::{Read, Write};
+    use std::sync::Arc;
+
+    let path = std::env::temp_dir().join(format!(
+        "tauri-plugin-serialport-recording-{}.jsonl",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let recorder = Arc::new(Recorder::new(&path).unwrap());
+    let mock = MockSerialPort::new(b"OK\r\n".to_vec());
+    let mut port = RecordingPort::new(Box::new(mock), "/dev/ttyMOCK0", recorder);
+    port.write_all(b"AT\r\n").unwrap();
+    let mut buf = [0; 8];
+    let size = port.read(&mut buf).unwrap();
+    assert_eq!(&buf[..size], b"OK\r\n");
+
+    let state = load_recording(&path).unwrap();
+    let mut serialports = state.serialports.lock().unwrap();
+    let replay = &mut serialports.get_mut("/dev/ttyMOCK0").unwrap().serialport;
+    assert_eq!(replay.bytes_to_read().unwrap(), 4);
+    let size = replay.read(&mut buf).unwrap();
+    assert_eq!(&buf[..size], b"OK\r\n");
+    drop(serialports);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn slip_frames_are_decoded() {
+    let mut framer = Framer::Slip {
+        buffer: vec![],
+        escaped: false,
+        invalid: false,
+        errors: vec![],
+    };
+    assert_eq!(framer.push(&[0xC0, 0x01, 0xDB]), Vec::<Vec<u8>>::new());
+    assert_eq!(
+        framer.push(&[0xDC, 0xDB, 0xDD, 0xC0, 0x02, 0xC0]),
+        vec![vec![0x01, 0xC0, 0xDB], vec![0x02]]
+    );
+    assert!(framer.take_errors().is_empty());
+    assert_eq!(
+        framer.push(&[0x03, 0xDB, 0x04, 0x05, 0xC0, 0x06, 0xC0]),
+        vec![vec![0x06]]
+    );
+    assert_eq!(framer.take_errors().len(), 1);
+}
+
+#[test]
+fn crc_matches_the_check_values() {
+    let data = b"123456789";
+    assert_eq!(Crc::Crc8.compute(data), 0xF4);
+    assert_eq!(Crc::Crc16Ccitt.compute(data), 0x29B1);
+    assert_eq!(Crc::Crc32.compute(data), 0xCBF4_3926);
+    assert_eq!(Crc::Crc16Ccitt.to_bytes(data), [0x29, 0xB1]);
+    assert_eq!(Crc::Crc16Ccitt.check(b"123456789\x29\xB1"), Some(&data[..]));
+    assert_eq!(Crc::Crc16Ccitt.check(b"123456789\xB1\x29"), None);
+    assert_eq!(Crc::Crc16Modbus.compute(data), 0x4B37);
+    assert_eq!(Crc::Crc16Modbus.to_bytes(data), [0x37, 0x4B]);
+    assert_eq!(Crc::from_name("crc32"), Some(Crc::Crc32));
+}
+
+#[test]
+fn length_prefixed_frames_are_split() {
+    let mut framer = Framer::LengthPrefix {
+        field_size: 2,
+        big_endian: true,
+        buffer: vec![],
+    };
+    assert_eq!(framer.push(&[0x00, 0x02, 0xAA]), Vec::<Vec<u8>>::new());
+    assert_eq!(
+        framer.push(&[0xBB, 0x00, 0x00, 0x00, 0x01, 0xCC, 0x00]),
+        vec![vec![0xAA, 0xBB], vec![], vec![0xCC]]
+    );
+    let mut framer = Framer::LengthPrefix {
+        field_size: 4,
+        big_endian: false,
+        buffer: vec![],
+    };
+    assert_eq!(
+        framer.push(&[0x01, 0x00, 0x00, 0x00, 0xDD]),
+        vec![vec![0xDD]]
+    );
+}
+
+#[test]
+fn modbus_rtu_frames_end_with_a_pause() {
+    let request = [0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD];
+    let mut framer = Framer::ModbusRtu {
+        silence: Duration::from_millis(20),
+        buffer: vec![],
+        last_read_at: None,
+        errors: vec![],
+    };
+    assert!(framer.push(&request[..3]).is_empty());
+    assert!(framer.push(&request[3..]).is_empty());
+    assert!(framer.poll().is_empty());
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(framer.poll(), vec![request.to_vec()]);
+    assert!(framer.poll().is_empty());
+    framer.push(&request[1..]);
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(framer.push(&request), Vec::<Vec<u8>>::new());
+    assert_eq!(framer.take_errors().len(), 1);
+}
+
+#[test]
+fn cobs_round_trips() {
+    assert_eq!(
+        cobs_encode(&[0x11, 0x00, 0x00, 0x22]),
+        [0x02, 0x11, 0x01, 0x02, 0x22]
+    );
+    let long: Vec<u8> = (0..600).map(|i| (i % 7) as u8).collect();
+    for data in [vec![], vec![0x00], vec![0x01; 254], long] {
+        assert_eq!(cobs_decode(&cobs_encode(&data)).unwrap(), data);
+    }
+    assert_eq!(cobs_decode(&[0x05, 0x11]), None);
+
+    let mut framer = Framer::Cobs {
+        buffer: vec![],
+        errors: vec![],
+    };
+    assert_eq!(
+        framer.push(&[0x02, 0x11, 0x01, 0x00, 0x03, 0x11]),
+        vec![vec![0x11, 0x00]]
+    );
+    assert_eq!(framer.push(&[0x00]), Vec::<Vec<u8>>::new());
+    assert_eq!(framer.take_errors().len(), 1);
+}
+
+#[test]
+fn nmea_sentences_are_checked() {
+    let mut framer = Framer::Nmea {
+        buffer: vec![],
+        errors: vec![],
+    };
+    assert_eq!(
+        framer.push(b"noise$GPGLL,4916.45,N,12311.12,W,225444,A*31\r\n$GPGLL,1*00\r"),
+        vec![b"$GPGLL,4916.45,N,12311.12,W,225444,A*31".to_vec()]
+    );
+    assert_eq!(framer.take_errors(), Vec::<String>::new());
+    assert_eq!(
+        framer.push(b"\n$PGRMZ,246,f\r\n"),
+        vec![b"$PGRMZ,246,f".to_vec()]
+    );
+    assert_eq!(framer.take_errors().len(), 1);
+}
+
+#[test]
+fn text_is_encoded_in_the_requested_charset() {
+    assert_eq!(encode_text("café", "latin-1", true).unwrap(), b"caf\xe9");
+    assert_eq!(encode_text("€5", "windows-1252", true).unwrap(), b"\x805");
+    assert_eq!(
+        encode_text("café", "utf-8", true).unwrap(),
+        "café".as_bytes()
+    );
+    assert_eq!(encode_text("café", "ascii", false).unwrap(), b"caf?");
+    assert!(encode_text("café", "ascii", true).is_err());
+    assert_eq!(encode_text("1€", "windows-1250", false).unwrap(), b"1\x80");
+    assert!(encode_text("中", "windows-1252", true).is_err());
+    assert!(encode_text("x", "klingon", false).is_err());
+}
+
+#[test]
+fn hex_dumps_show_offset_hex_and_ascii() {
+    assert_eq!(format_hex_dump(b"Hello"), "0000: 48 65 6C 6C 6F  Hello");
+    assert_eq!(
+        format_hex_dump(b"0123456789abcdef\r\n"),
+        "0000: 30 31 32 33 34 35 36 37 38 39 61 62 63 64 65 66  0123456789abcdef\n\
+         0010: 0D 0A                                            .."
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn sysfs_usb_ports_are_read_from_the_usb_device() {
+    use crate::sysfs::usb_ports;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+
+    let root = std::env::temp_dir().join(format!("serialport-sysfs-{}", std::process::id()));
+    let usb_device = root.join("devices/usb1/1-1");
+    let tty_device = usb_device.join("1-1:1.0/ttyUSB0");
+    let uart_device = root.join("devices/platform/serial8250/tty/ttyS0");
+    fs::create_dir_all(&tty_device).unwrap();
+    fs::create_dir_all(&uart_device).unwrap();
+    fs::write(usb_device.join("idVendor"), "0403\n").unwrap();
+    fs::write(usb_device.join("idProduct"), "6001\n").unwrap();
+    fs::write(usb_device.join("manufacturer"), "FTDI\n").unwrap();
+    fs::write(usb_device.join("serial"), "A50285BI\n").unwrap();
+    for (name, device) in [("ttyUSB0", &tty_device), ("ttyS0", &uart_device)] {
+        fs::create_dir_all(root.join("class/tty").join(name)).unwrap();
+        symlink(device, root.join("class/tty").join(name).join("device")).unwrap();
+    }
+
+    let ports = usb_ports(&root.join("class/tty"));
+    let device_path = fs::canonicalize(&usb_device).unwrap();
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(ports.len(), 1);
+    assert_eq!(ports[0].port_name, "/dev/ttyUSB0");
+    assert_eq!(ports[0].usb_vid, Some(0x0403));
+    assert_eq!(ports[0].usb_pid, Some(0x6001));
+    assert_eq!(ports[0].usb_manufacturer.as_deref(), Some("FTDI"));
+    assert_eq!(ports[0].usb_serial_number.as_deref(), Some("A50285BI"));
+    assert_eq!(ports[0].usb_product, None);
+    assert_eq!(ports[0].device_path.as_deref(), device_path.to_str());
+}
+
+mod commands {
+    use crate::command::*;
+    use crate::error::Error;
+    use crate::mock::MockSerialPort;
+    use crate::state::{OpenParams, ReadOptions, SerialportPluginConfig, SerialportState};
+    use std::sync::{Arc, Mutex};
+    use tauri::test::{mock_app, MockRuntime};
+    use tauri::{async_runtime::block_on, App, Manager, Window, WindowBuilder, WindowUrl};
+
+    const PATH: &str = "/dev/ttyMOCK0";
+
+    /// An app whose state has a mock port open at `PATH` that reads `read_data`.
+    fn mock_session(read_data: &[u8]) -> (App<MockRuntime>, Window<MockRuntime>, MockSerialPort) {
+        let app = mock_app();
+        let mock = MockSerialPort::new(read_data.to_vec());
+        app.manage(SerialportState::with_port(PATH, Box::new(mock.clone())));
+        let window = app.get_window("main").unwrap();
+        (app, window, mock)
+    }
+
+    fn path() -> String {
+        PATH.to_string()
+    }
+
+    /// Collects the data of a read loop.
+    #[derive(Clone, Default)]
+    struct CollectSink(Arc<Mutex<Vec<Vec<u8>>>>);
+
+    impl ReadSink for CollectSink {
+        fn data(&mut self, data: &[u8], _timestamp_ms: u64) -> bool {
+            self.0.lock().unwrap().push(data.to_vec());
+            true
+        }
+
+        fn error(&mut self, _error: &std::io::Error) {}
+    }
+
+    /// Start a read loop of the port at `PATH` passing its data to a `CollectSink`.
+    fn collect_reads(app: &App<MockRuntime>, options: ReadOptions) -> CollectSink {
+        let sink = CollectSink::default();
+        read_into(app, PATH, sink.clone(), options);
+        sink
+    }
+
+    /// Start a read loop of the port at `path` passing what it reads to `sink`.
+    fn read_into<S: ReadSink>(app: &App<MockRuntime>, path: &str, sink: S, options: ReadOptions) {
+        let state = app.state::<SerialportState>();
+        let mut serialports = state.serialports.lock().unwrap();
+        start_read(
+            serialports.get_mut(path).unwrap(),
+            sink,
+            &state.pool,
+            state.serialports.clone(),
+            path.to_string(),
+            options,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn available_ports_are_sorted() {
+        let ports = available_ports();
+        let mut sorted = ports.clone();
+        sorted.sort();
+        assert_eq!(ports, sorted);
+        let names: Vec<String> = available_ports_info()
+            .into_iter()
+            .map(|port| port.port_name)
+            .collect();
+        let mut sorted_names = names.clone();
+        sorted_names.sort();
+        assert_eq!(names, sorted_names);
+        // sysfs may add USB ports on Linux
+        assert!(ports.iter().all(|port| names.contains(port)));
+    }
+
+    #[test]
+    fn available_ports_filtered_checks_the_type() {
+        assert_eq!(available_ports_filtered(None).unwrap(), available_ports());
+        let usb = available_ports_filtered(Some("USB".to_string())).unwrap();
+        assert!(usb.iter().all(|port| available_ports().contains(port)));
+        let result = available_ports_filtered(Some("usb".to_string()));
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn bytes_to_read_counts_pending_data() {
+        let (app, window, _) = mock_session(b"abc");
+        assert_eq!(
+            bytes_to_read(app.handle(), window, app.state(), path()).unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn bytes_to_write_is_zero_when_sent() {
+        let (app, window, _) = mock_session(b"");
+        assert_eq!(
+            bytes_to_write(app.handle(), window, app.state(), path()).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn unknown_path_is_not_found() {
+        let (app, window, _) = mock_session(b"");
+        let result = bytes_to_read(app.handle(), window, app.state(), "/dev/none".to_string());
+        assert!(matches!(result, Err(Error::PortNotFound(_))));
+    }
+
+    #[test]
+    fn read_emits_until_cancelled() {
+        let (app, window, mock) = mock_session(b"hello");
+        read(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            Some(10),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        mock.push_read_data(b"!");
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let stats = get_statistics(app.handle(), window.clone(), app.state(), path()).unwrap();
+        assert_eq!(stats.bytes_read, 6);
+        block_on(cancel_read(app.handle(), window, app.state(), path())).unwrap();
+    }
+
+    #[test]
+    fn read_retries_interrupted_and_empty_reads() {
+        let (app, window, mock) = mock_session(b"ok");
+        mock.push_read_error(std::io::ErrorKind::Interrupted);
+        mock.push_read_error(std::io::ErrorKind::WouldBlock);
+        let sink = collect_reads(
+            &app,
+            ReadOptions {
+                timeout: Some(10),
+                ..ReadOptions::default()
+            },
+        );
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(*sink.0.lock().unwrap(), vec![b"ok".to_vec()]);
+        let stats = get_statistics(app.handle(), window.clone(), app.state(), path()).unwrap();
+        assert_eq!(stats.read_errors, 0);
+        block_on(cancel_read(app.handle(), window, app.state(), path())).unwrap();
+    }
+
+    #[test]
+    fn read_emits_delimited_frames_per_packet() {
+        let (app, window, _) = mock_session(b"a\nb\nc");
+        let sink = collect_reads(
+            &app,
+            ReadOptions {
+                timeout: Some(10),
+                delimiter: Some(b"\n".to_vec()),
+                emit_per_packet: Some(true),
+                ..ReadOptions::default()
+            },
+        );
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(
+            *sink.0.lock().unwrap(),
+            vec![b"a\n".to_vec(), b"b\n".to_vec()]
+        );
+        block_on(cancel_read(app.handle(), window, app.state(), path())).unwrap();
+    }
+
+    #[test]
+    fn read_batches_data_for_batch_ms() {
+        let (app, window, mock) = mock_session(b"ab");
+        let sink = collect_reads(
+            &app,
+            ReadOptions {
+                timeout: Some(10),
+                batch_ms: Some(150),
+                ..ReadOptions::default()
+            },
+        );
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        mock.push_read_data(b"c");
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        assert_eq!(*sink.0.lock().unwrap(), vec![b"abc".to_vec()]);
+        block_on(cancel_read(app.handle(), window, app.state(), path())).unwrap();
+    }
+
+    #[test]
+    fn read_drops_data_while_events_are_pending() {
+        let (app, window, mock) = mock_session(b"ab");
+        read(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            Some(10),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(1),
+            None,
+            None,
+        )
+        .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        mock.push_read_data(b"c");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        acknowledge_read(app.handle(), window.clone(), app.state(), path(), None).unwrap();
+        mock.push_read_data(b"d");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        let stats = get_statistics(app.handle(), window.clone(), app.state(), path()).unwrap();
+        assert_eq!(stats.bytes_read, 4);
+        assert_eq!(stats.dropped_bytes, 1);
+        block_on(cancel_read(app.handle(), window, app.state(), path())).unwrap();
+    }
+
+    #[test]
+    fn read_rejects_an_event_name_in_use() {
+        let (app, window, _) = mock_session(b"");
+        app.state::<SerialportState>()
+            .insert_port("/dev/mock1", Box::new(MockSerialPort::new(Vec::new())));
+        let read_as = |path: &str| {
+            read(
+                app.handle(),
+                window.clone(),
+                app.state(),
+                path.to_string(),
+                Some(10),
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("sensor".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+        read_as(PATH).unwrap();
+        assert!(matches!(
+            read_as("/dev/mock1"),
+            Err(Error::InvalidArgument(_))
+        ));
+        block_on(cancel_read(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+        ))
+        .unwrap();
+        read_as("/dev/mock1").unwrap();
+        block_on(cancel_read(
+            app.handle(),
+            window,
+            app.state(),
+            "/dev/mock1".to_string(),
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn access_mode_is_enforced() {
+        let (app, window, mock) = mock_session(b"");
+        let set_access_mode = |access_mode| {
+            if let Some(serialport_info) = app
+                .state::<SerialportState>()
+                .serialports
+                .lock()
+                .unwrap()
+                .get_mut(PATH)
+            {
+                serialport_info.access_mode = access_mode;
+            }
+        };
+        set_access_mode(crate::state::AccessMode::ReadOnly);
+        let result = write_binary(app.handle(), window.clone(), app.state(), path(), vec![1]);
+        assert!(matches!(result, Err(Error::AccessDenied { .. })));
+        assert!(mock.written().is_empty());
+        set_access_mode(crate::state::AccessMode::WriteOnly);
+        let result = read(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            Some(10),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(matches!(result, Err(Error::AccessDenied { .. })));
+        write_binary(app.handle(), window, app.state(), path(), vec![1]).unwrap();
+        assert_eq!(mock.written(), [1]);
+    }
+
+    #[test]
+    fn read_to_unknown_window_is_rejected() {
+        let (app, window, _) = mock_session(b"");
+        let result = read(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            Some(10),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some("settings".to_string()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+        let state = app.state::<SerialportState>();
+        assert!(state.serialports.lock().unwrap()[PATH].sender.is_none());
+    }
+
+    #[test]
+    fn idle_port_is_closed_by_the_read_loop() {
+        let (app, window, _) = mock_session(b"");
+        if let Some(serialport_info) = app
+            .state::<SerialportState>()
+            .serialports
+            .lock()
+            .unwrap()
+            .get_mut(PATH)
+        {
+            serialport_info.idle_timeout = Some(std::time::Duration::from_millis(50));
+        }
+        read(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            Some(10),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        assert!(!is_open(app.handle(), window, app.state(), path()));
+    }
+
+    #[test]
+    fn reads_started_together_get_a_worker_each() {
+        let (app, window, mock) = mock_session(b"");
+        let other = MockSerialPort::new(Vec::new());
+        let state = app.state::<SerialportState>();
+        state.insert_port("/dev/ttyMOCK1", Box::new(other.clone()));
+        collect_reads(&app, ReadOptions::default());
+        block_on(cancel_read(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+        ))
+        .unwrap();
+        // give the worker of the cancelled loop time to become idle again
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        let options = ReadOptions {
+            timeout: Some(10),
+            ..Default::default()
+        };
+        let first = collect_reads(&app, options.clone());
+        let second = CollectSink::default();
+        read_into(&app, "/dev/ttyMOCK1", second.clone(), options);
+        mock.push_read_data(b"a");
+        other.push_read_data(b"b");
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_eq!(*first.0.lock().unwrap(), [b"a".to_vec()]);
+        assert_eq!(*second.0.lock().unwrap(), [b"b".to_vec()]);
+        block_on(cancel_read(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+        ))
+        .unwrap();
+        block_on(cancel_read(
+            app.handle(),
+            window,
+            app.state(),
+            "/dev/ttyMOCK1".into(),
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn cancel_read_without_read_is_ok() {
+        let (app, window, _) = mock_session(b"");
+        block_on(cancel_read(app.handle(), window, app.state(), path())).unwrap();
+    }
+
+    #[test]
+    fn clear_input_buffer_discards_pending_data() {
+        let (app, window, _) = mock_session(b"abc");
+        clear_input_buffer(app.handle(), window.clone(), app.state(), path()).unwrap();
+        assert_eq!(
+            bytes_to_read(app.handle(), window, app.state(), path()).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn close_removes_the_port() {
+        let (app, window, _) = mock_session(b"");
+        close(app.handle(), window.clone(), app.state(), path()).unwrap();
+        assert!(!is_open(app.handle(), window.clone(), app.state(), path()));
+        let result = close(app.handle(), window, app.state(), path());
+        assert!(matches!(result, Err(Error::PortNotFound(_))));
+    }
+
+    #[test]
+    fn close_all_removes_every_port() {
+        let (app, window, _) = mock_session(b"");
+        close_all(app.handle(), window.clone(), app.state()).unwrap();
+        assert!(list_open_ports(app.handle(), window, app.state()).is_empty());
+    }
+
+    #[test]
+    fn close_all_and_list_open_ports_follow_the_window_acl() {
+        let app = mock_app();
+        let mut window_port_acl = std::collections::HashMap::new();
+        window_port_acl.insert("main".to_string(), vec![PATH.to_string()]);
+        app.manage(SerialportState {
+            config: SerialportPluginConfig {
+                window_port_acl: Some(window_port_acl),
+                ..Default::default()
+            },
+            ..SerialportState::with_mock(PATH, Vec::new())
+        });
+        let state = app.state::<SerialportState>();
+        state.insert_port("/dev/ttyMOCK1", Box::new(MockSerialPort::new(Vec::new())));
+        let window = app.get_window("main").unwrap();
+        assert_eq!(
+            list_open_ports(app.handle(), window.clone(), app.state()),
+            [PATH]
+        );
+        close_all(app.handle(), window, app.state()).unwrap();
+        let serialports = state.serialports.lock().unwrap();
+        assert!(serialports.contains_key("/dev/ttyMOCK1"));
+        assert!(!serialports.contains_key(PATH));
+    }
+
+    #[test]
+    fn close_all_on_exit_stops_reading() {
+        let (app, window, _) = mock_session(b"data");
+        collect_reads(&app, ReadOptions::default());
+        close_all_on_exit(&app.state::<SerialportState>());
+        assert!(list_open_ports(app.handle(), window, app.state()).is_empty());
+    }
+
+    #[cfg(all(feature = "pty", unix))]
+    #[test]
+    fn virtual_port_pair_round_trips_through_commands() {
+        let (app, window, _) = mock_session(b"");
+        let (path_a, path_b) = create_virtual_port_pair(app.handle(), window.clone()).unwrap();
+        for path in [&path_a, &path_b] {
+            block_on(open(
+                app.handle(),
+                app.state(),
+                window.clone(),
+                path.clone(),
+                Some(115_200),
+                None,
+                None,
+                None,
+                None,
+                Some(1000),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ))
+            .unwrap();
+        }
+
+        let state = app.state::<SerialportState>();
+        // the bridge between the ports may hand the data over in pieces
+        let read_4 = |path: &str| {
+            let mut handle = state.get_handle(path).unwrap();
+            let mut buf = [0; 4];
+            let mut size = 0;
+            while size < buf.len() {
+                size += handle.read_blocking(&mut buf[size..]).unwrap();
+            }
+            buf
+        };
+        write(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path_a.clone(),
+            "ping".into(),
+        )
+        .unwrap();
+        assert_eq!(&read_4(&path_b), b"ping");
+
+        write(app.handle(), window, app.state(), path_b, "pong".into()).unwrap();
+        assert_eq!(&read_4(&path_a), b"pong");
+    }
+
+    #[cfg(not(all(feature = "pty", unix)))]
+    #[test]
+    fn virtual_port_pair_is_unsupported_without_pty() {
+        let (app, window, _) = mock_session(b"");
+        assert!(matches!(
+            create_virtual_port_pair(app.handle(), window),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn flush_succeeds() {
+        let (app, window, _) = mock_session(b"");
+        flush(app.handle(), window, app.state(), path()).unwrap();
+    }
+
+    #[test]
+    fn force_close_ignores_unknown_paths() {
+        let (app, window, _) = mock_session(b"");
+        force_close(app.handle(), window.clone(), app.state(), path()).unwrap();
+        force_close(app.handle(), window.clone(), app.state(), path()).unwrap();
+        assert!(!is_open(app.handle(), window, app.state(), path()));
+    }
+
+    #[test]
+    fn get_port_settings_returns_the_config() {
+        let (app, window, _) = mock_session(b"");
+        let config = get_port_settings(app.handle(), window, app.state(), path()).unwrap();
+        assert_eq!(config.baud_rate, 9600);
+        assert_eq!(config.data_bits, 8);
+        assert_eq!(config.parity, "None");
+    }
+
+    #[test]
+    fn get_statistics_counts_writes() {
+        let (app, window, _) = mock_session(b"");
+        write(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            "abcd".into(),
+        )
+        .unwrap();
+        let stats = get_statistics(app.handle(), window, app.state(), path()).unwrap();
+        assert_eq!(stats.bytes_written, 4);
+        assert_eq!(stats.write_count, 1);
+        assert_eq!(stats.read_count, 0);
+    }
+
+    #[test]
+    fn handle_uses_the_open_port() {
+        let (app, window, mock) = mock_session(b"ok");
+        let state = app.state::<SerialportState>();
+        let mut handle = state.get_handle(PATH).unwrap();
+        assert_eq!(handle.write(b"AT").unwrap(), 2);
+        let mut buf = [0; 8];
+        assert_eq!(handle.read_blocking(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"ok");
+        handle.close().unwrap();
+        assert_eq!(mock.written(), b"AT");
+        let stats = get_statistics(app.handle(), window, app.state(), path()).unwrap();
+        assert_eq!(stats.bytes_written, 2);
+        assert_eq!(stats.bytes_read, 2);
+        assert!(matches!(
+            state.get_handle("/dev/none"),
+            Err(Error::PortNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn get_port_type_reports_unlisted_ports_as_unknown() {
+        let (app, window, _) = mock_session(b"");
+        let port_type = get_port_type(app.handle(), window.clone(), app.state(), path()).unwrap();
+        assert_eq!(port_type, "Unknown");
+        let port_type =
+            get_port_type(app.handle(), window, app.state(), "/dev/none".into()).unwrap();
+        assert_eq!(port_type, "Unknown");
+    }
+
+    #[test]
+    fn write_integers_use_their_byte_order() {
+        let (app, window, mock) = mock_session(b"");
+        write_u8(app.handle(), window.clone(), app.state(), path(), 0x01).unwrap();
+        write_u16_be(app.handle(), window.clone(), app.state(), path(), 0x0203).unwrap();
+        write_u16_le(app.handle(), window.clone(), app.state(), path(), 0x0203).unwrap();
+        write_u32_be(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            0x04050607,
+        )
+        .unwrap();
+        write_u32_le(app.handle(), window, app.state(), path(), 0x04050607).unwrap();
+        assert_eq!(mock.written(), [1, 2, 3, 3, 2, 4, 5, 6, 7, 7, 6, 5, 4]);
+    }
+
+    #[test]
+    fn write_binary_slice_checks_the_range() {
+        let (app, window, mock) = mock_session(b"");
+        let value = vec![1, 2, 3, 4, 5];
+        let written = write_binary_slice(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            value.clone(),
+            1,
+            3,
+        )
+        .unwrap();
+        assert_eq!(written, 3);
+        assert_eq!(mock.written(), [2, 3, 4]);
+        assert!(matches!(
+            write_binary_slice(app.handle(), window, app.state(), path(), value, 4, 2),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn send_xoff_and_xon_write_control_characters() {
+        let (app, window, mock) = mock_session(b"");
+        send_xoff(app.handle(), window.clone(), app.state(), path()).unwrap();
+        send_xon(app.handle(), window, app.state(), path()).unwrap();
+        assert_eq!(mock.written(), [0x13, 0x11]);
+    }
+
+    #[test]
+    fn write_repeated_writes_count_bytes() {
+        let (app, window, mock) = mock_session(b"");
+        let written = block_on(write_repeated(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            0x55,
+            300,
+            None,
+        ))
+        .unwrap();
+        assert_eq!(written, 300);
+        let written = block_on(write_repeated(
+            app.handle(),
+            window,
+            app.state(),
+            path(),
+            0xAA,
+            3,
+            Some(1),
+        ))
+        .unwrap();
+        assert_eq!(written, 3);
+        let data = mock.written();
+        assert_eq!(data.len(), 303);
+        assert!(data[..300].iter().all(|&byte| byte == 0x55));
+        assert_eq!(data[300..], [0xAA; 3]);
+    }
+
+    #[test]
+    fn read_discard_drains_pending_input() {
+        let (app, window, mock) = mock_session(&[0x42; 2000]);
+        let discarded = read_discard(app.handle(), window.clone(), app.state(), path()).unwrap();
+        assert_eq!(discarded, 2000);
+        mock.push_read_data(b"ok");
+        assert_eq!(
+            read_discard(app.handle(), window, app.state(), path()).unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn peek_keeps_bytes_for_the_next_read() {
+        let (app, window, _) = mock_session(b"hello");
+        let peek_at = |count| peek(app.handle(), window.clone(), app.state(), path(), count);
+        assert_eq!(peek_at(2).unwrap(), b"he");
+        assert_eq!(peek_at(3).unwrap(), b"hel");
+        assert_eq!(peek_at(10).unwrap(), b"hello");
+        assert_eq!(
+            bytes_to_read(app.handle(), window.clone(), app.state(), path()).unwrap(),
+            5
+        );
+        let mut handle = app.state::<SerialportState>().get_handle(PATH).unwrap();
+        let mut buf = [0; 3];
+        assert_eq!(handle.read_blocking(&mut buf).unwrap(), 3);
+        assert_eq!(&buf, b"hel");
+        assert_eq!(peek_at(10).unwrap(), b"lo");
+    }
+
+    #[test]
+    fn close_emits_port_closed() {
+        let app = mock_app();
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let events = closed.clone();
+        app.manage(SerialportState {
+            port_event_hook: Some(Arc::new(move |event: &str, path: &str| {
+                events.lock().unwrap().push(format!("{} {}", event, path));
+            })),
+            ..SerialportState::with_mock(PATH, Vec::new())
+        });
+        let window = app.get_window("main").unwrap();
+        close(app.handle(), window.clone(), app.state(), path()).unwrap();
+        force_close(app.handle(), window, app.state(), path()).unwrap();
+        assert_eq!(
+            *closed.lock().unwrap(),
+            [format!("plugin-serialport-port-closed {}", PATH)]
+        );
+    }
+
+    #[test]
+    fn leases_reserve_a_port_for_one_window() {
+        let (app, window, mock) = mock_session(b"");
+        let other = WindowBuilder::new(&app, "other", WindowUrl::default())
+            .build()
+            .unwrap();
+        let lease = acquire_lease(app.handle(), other.clone(), app.state(), path(), None).unwrap();
+        let result = write(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            "a".into(),
+        );
+        assert!(matches!(result, Err(Error::PortLeased(_))));
+        let result = acquire_lease(app.handle(), window.clone(), app.state(), path(), None);
+        assert!(matches!(result, Err(Error::PortLeased(_))));
+        release_lease(app.handle(), window.clone(), app.state(), lease).unwrap();
+        write(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            "b".into(),
+        )
+        .unwrap();
+        acquire_lease(app.handle(), other, app.state(), path(), Some(0)).unwrap();
+        write(app.handle(), window, app.state(), path(), "c".into()).unwrap();
+        assert_eq!(mock.written(), b"bc");
+    }
+
+    #[test]
+    fn leases_guard_settings_and_control_lines() {
+        let (app, window, _) = mock_session(b"");
+        let other = WindowBuilder::new(&app, "other", WindowUrl::default())
+            .build()
+            .unwrap();
+        acquire_lease(app.handle(), other.clone(), app.state(), path(), None).unwrap();
+        let result = set_baud_rate(app.handle(), window.clone(), app.state(), path(), 19200);
+        assert!(matches!(result, Err(Error::PortLeased(_))));
+        let result =
+            write_data_terminal_ready(app.handle(), window.clone(), app.state(), path(), true);
+        assert!(matches!(result, Err(Error::PortLeased(_))));
+        let result = peek(app.handle(), window.clone(), app.state(), path(), 1);
+        assert!(matches!(result, Err(Error::PortLeased(_))));
+        let result = flush(app.handle(), window, app.state(), path());
+        assert!(matches!(result, Err(Error::PortLeased(_))));
+        set_baud_rate(app.handle(), other, app.state(), path(), 19200).unwrap();
+    }
+
+    #[test]
+    fn close_inactive_ports_closes_idle_ports() {
+        let (app, window, _) = mock_session(b"");
+        let closed =
+            close_inactive_ports(app.handle(), window.clone(), app.state(), 60_000).unwrap();
+        assert!(closed.is_empty());
+        let closed = close_inactive_ports(app.handle(), window.clone(), app.state(), 0).unwrap();
+        assert_eq!(closed, [PATH]);
+        assert!(!is_open(app.handle(), window, app.state(), path()));
+    }
+
+    #[test]
+    fn batch_open_reports_each_port() {
+        let (app, window, _) = mock_session(b"");
+        let params = |path: &str| OpenParams {
+            path: path.to_string(),
+            baud_rate: None,
+            data_bits: None,
+            flow_control: None,
+            parity: None,
+            stop_bits: None,
+            timeout: None,
+            idle_timeout_ms: None,
+            access_mode: None,
+            validate_baud_rate: None,
+            inter_char_timeout_ms: None,
+            init_sequence: None,
+            init_delay_ms: None,
+        };
+        let invalid = OpenParams {
+            parity: Some("mark".to_string()),
+            ..params("/dev/none")
+        };
+        let results = block_on(batch_open(
+            app.handle(),
+            app.state(),
+            window.clone(),
+            vec![params(PATH), invalid.clone()],
+            None,
+        ))
+        .unwrap();
+        assert!(matches!(results[0], Err(Error::AlreadyOpen(_))));
+        assert!(matches!(results[1], Err(Error::InvalidArgument(_))));
+        let result = block_on(batch_open(
+            app.handle(),
+            app.state(),
+            window,
+            vec![invalid, params(PATH)],
+            Some(true),
+        ));
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn scan_baud_rate_rejects_open_ports() {
+        let (app, window, _) = mock_session(b"");
+        let result = block_on(scan_baud_rate(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            b"AT\r".to_vec(),
+            b"OK".to_vec(),
+            100,
+        ));
+        assert!(matches!(result, Err(Error::AlreadyOpen(_))));
+        let result = block_on(scan_baud_rate(
+            app.handle(),
+            window,
+            app.state(),
+            "/dev/none".into(),
+            b"AT\r".to_vec(),
+            Vec::new(),
+            100,
+        ));
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn open_tcp_registers_a_network_port() {
+        use std::io::{Read, Write};
+
+        let (app, window, _) = mock_session(b"");
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        open_tcp(
+            app.handle(),
+            app.state(),
+            window.clone(),
+            "127.0.0.1".into(),
+            port,
+            Some(115_200),
+            None,
+        )
+        .unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        let path = format!("tcp://127.0.0.1:{}", port);
+        write(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path.clone(),
+            "AT".into(),
+        )
+        .unwrap();
+        let mut buf = [0; 2];
+        server.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"AT");
+        server.write_all(b"OK").unwrap();
+        let mut handle = app.state::<SerialportState>().get_handle(&path).unwrap();
+        let mut buf = [0; 8];
+        assert_eq!(handle.read_blocking(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"OK");
+        let settings = get_port_settings(app.handle(), window, app.state(), path).unwrap();
+        assert_eq!(settings.baud_rate, 115_200);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn open_pipe_is_windows_only() {
+        let (app, window, _) = mock_session(b"");
+        let result = open_pipe(
+            app.handle(),
+            app.state(),
+            window.clone(),
+            "com1".to_string(),
+            None,
+            None,
+        );
+        assert!(matches!(result, Err(Error::IoError(_))));
+        assert!(!is_open(
+            app.handle(),
+            window,
+            app.state(),
+            r"\\.\pipe\com1".to_string()
+        ));
+    }
+
+    #[test]
+    fn open_bluetooth_checks_address_and_channel() {
+        let (app, window, _) = mock_session(b"");
+        let open = |address: &str, channel| {
+            open_bluetooth(
+                app.handle(),
+                app.state(),
+                window.clone(),
+                address.to_string(),
+                channel,
+                None,
+                None,
+            )
+        };
+        assert!(matches!(
+            open("00:11:22:AA:BB", 1),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            open("00:11:22:AA:BB:GG", 1),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            open("00:11:22:AA:BB:CC", 0),
+            Err(Error::InvalidArgument(_))
+        ));
+        #[cfg(not(any(target_os = "linux", windows)))]
+        assert!(matches!(
+            open("00-11-22-aa-bb-cc", 1),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn at_command_strips_the_echo() {
+        let (app, window, mock) = mock_session(b"AT+CSQ\r\r\n+CSQ: 20,99\r\n\r\nOK\r\nRING");
+        let response = block_on(at_command(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            "AT+CSQ".into(),
+            1000,
+        ))
+        .unwrap();
+        assert_eq!(response, "+CSQ: 20,99\r\n\r\nOK");
+        assert_eq!(mock.written(), b"AT+CSQ\r\n");
+        mock.push_read_data(b"\r\nERROR\r\n");
+        let result = block_on(at_init(app.handle(), window, app.state(), path(), None));
+        assert!(matches!(result, Err(Error::IoError(_))));
+    }
+
+    #[test]
+    fn at_command_resumes_the_read_loop_when_it_fails() {
+        let (app, window, _) = mock_session(b"");
+        collect_reads(
+            &app,
+            ReadOptions {
+                timeout: Some(10),
+                ..Default::default()
+            },
+        );
+        let result = block_on(at_command(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            "AT".into(),
+            50,
+        ));
+        assert!(result.is_err());
+        let state = app.state::<SerialportState>();
+        assert!(state.serialports.lock().unwrap()[PATH].sender.is_some());
+        block_on(cancel_read(app.handle(), window, app.state(), path())).unwrap();
+    }
+
+    #[test]
+    fn read_reports_silence_once_until_data_arrives() {
+        /// Counts the silences a read loop reports.
+        #[derive(Clone, Default)]
+        struct SilenceSink(Arc<Mutex<u32>>);
+
+        impl ReadSink for SilenceSink {
+            fn data(&mut self, _data: &[u8], _timestamp_ms: u64) -> bool {
+                true
+            }
+
+            fn error(&mut self, _error: &std::io::Error) {}
+
+            fn silence(&mut self, _duration_ms: u64) {
+                *self.0.lock().unwrap() += 1;
+            }
+        }
+
+        let (app, window, mock) = mock_session(b"");
+        let sink = SilenceSink::default();
+        read_into(
+            &app,
+            PATH,
+            sink.clone(),
+            ReadOptions {
+                timeout: Some(10),
+                silence_timeout_ms: Some(50),
+                ..Default::default()
+            },
+        );
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_eq!(*sink.0.lock().unwrap(), 1);
+        mock.push_read_data(b"!");
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert_eq!(*sink.0.lock().unwrap(), 2);
+        block_on(cancel_read(app.handle(), window, app.state(), path())).unwrap();
+    }
+
+    #[test]
+    fn is_open_reports_open_ports() {
+        let (app, window, _) = mock_session(b"");
+        assert!(is_open(app.handle(), window.clone(), app.state(), path()));
+        assert!(!is_open(
+            app.handle(),
+            window,
+            app.state(),
+            "/dev/none".into()
+        ));
+    }
+
+    #[test]
+    fn list_open_ports_lists_the_mock() {
+        let (app, window, _) = mock_session(b"");
+        assert_eq!(
+            list_open_ports(app.handle(), window, app.state()),
+            vec![path()]
+        );
+    }
+
+    #[test]
+    fn open_rejects_an_open_path() {
+        let (app, window, _) = mock_session(b"");
+        let result = block_on(open(
+            app.handle(),
+            app.state(),
+            window.clone(),
+            path(),
+            Some(9600),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ));
+        assert!(matches!(result, Err(Error::AlreadyOpen(_))));
+        let result = block_on(open(
+            app.handle(),
+            app.state(),
+            window.clone(),
+            "/dev/none".into(),
+            Some(250_001),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+            None,
+            None,
+            None,
+        ));
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+        let result = block_on(open(
+            app.handle(),
+            app.state(),
+            window,
+            "/dev/none".into(),
+            Some(9600),
+            None,
+            None,
+            Some("mark".into()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        ));
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn open_with_defaults_rejects_an_open_path() {
+        let (app, window, _) = mock_session(b"");
+        let result = block_on(open_with_defaults(
+            app.handle(),
+            app.state(),
+            window,
+            path(),
+            115_200,
+        ));
+        assert!(matches!(result, Err(Error::AlreadyOpen(_))));
+    }
+
+    #[test]
+    fn open_follows_the_plugin_config() {
+        let app = mock_app();
+        app.manage(SerialportState {
+            config: SerialportPluginConfig {
+                allowed_ports: Some(vec![PATH.to_string(), "/dev/ttyMOCK1".to_string()]),
+                max_open_ports: Some(1),
+                ..Default::default()
+            },
+            ..SerialportState::with_mock(PATH, Vec::new())
+        });
+        let window = app.get_window("main").unwrap();
+        let result = block_on(open_with_defaults(
+            app.handle(),
+            app.state(),
+            window.clone(),
+            "/dev/none".into(),
+            9600,
+        ));
+        assert!(matches!(result, Err(Error::PermissionDenied(_))));
+        let result = block_on(open_with_defaults(
+            app.handle(),
+            app.state(),
+            window,
+            "/dev/ttyMOCK1".into(),
+            9600,
+        ));
+        assert!(matches!(result, Err(Error::TooManyPorts(1))));
+    }
+
+    #[test]
+    fn port_info_combines_settings_stats_and_signals() {
+        let (app, window, _) = mock_session(b"");
+        write_data_terminal_ready(app.handle(), window.clone(), app.state(), path(), true).unwrap();
+        let info = port_info(app.handle(), window, app.state(), path()).unwrap();
+        assert_eq!(info.config.baud_rate, 9600);
+        assert_eq!(info.stats.bytes_written, 0);
+        assert!(info.signals.data_set_ready);
+        assert!(!info.signals.ring_indicator);
+        assert!(!info.is_reading);
+    }
+
+    #[test]
+    fn init_sequence_is_written_before_the_port_is_used() {
+        let (app, _window, mock) = mock_session(b"");
+        let state = app.state::<SerialportState>();
+        // `open` initializes the port before registering it
+        let mut serialport_info = state.serialports.lock().unwrap().remove(PATH).unwrap();
+        initialize_port(&mut serialport_info, PATH, b"AT\r", Some(10)).unwrap();
+        assert_eq!(mock.written(), b"AT\r");
+        assert!(serialport_info.initialized);
+
+        serialport_info.initialized = false;
+        serialport_info.access_mode = crate::state::AccessMode::ReadOnly;
+        let result = initialize_port(&mut serialport_info, PATH, b"AT\r", None);
+        assert!(matches!(result, Err(Error::AccessDenied { .. })));
+        assert!(!serialport_info.initialized);
+    }
+
+    #[test]
+    fn read_raw_reads_once_without_the_read_loop() {
+        let (app, _window, mock) = mock_session(b"hello");
+        let state = app.state::<SerialportState>();
+        assert_eq!(crate::read_raw(&state, PATH, 3, 10).unwrap(), b"hel");
+        assert_eq!(crate::read_raw(&state, PATH, 16, 10).unwrap(), b"lo");
+        assert!(crate::read_raw(&state, PATH, 16, 10).unwrap().is_empty());
+        assert_eq!(
+            serialport::SerialPort::timeout(&mock),
+            std::time::Duration::from_millis(200)
+        );
+        assert!(matches!(
+            crate::read_raw(&state, "/dev/none", 16, 10),
+            Err(Error::PortNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn read_carrier_detect_is_low() {
+        let (app, window, _) = mock_session(b"");
+        assert!(!read_carrier_detect(app.handle(), window, app.state(), path()).unwrap());
+    }
+
+    #[test]
+    fn read_clear_to_send_follows_request_to_send() {
+        let (app, window, _) = mock_session(b"");
+        write_request_to_send(app.handle(), window.clone(), app.state(), path(), true).unwrap();
+        assert!(read_clear_to_send(app.handle(), window, app.state(), path()).unwrap());
+    }
+
+    #[test]
+    fn read_data_set_ready_follows_data_terminal_ready() {
+        let (app, window, _) = mock_session(b"");
+        write_data_terminal_ready(app.handle(), window.clone(), app.state(), path(), true).unwrap();
+        assert!(read_data_set_ready(app.handle(), window, app.state(), path()).unwrap());
+    }
+
+    #[test]
+    fn read_ring_indicator_is_low() {
+        let (app, window, _) = mock_session(b"");
+        assert!(!read_ring_indicator(app.handle(), window, app.state(), path()).unwrap());
+    }
+
+    #[test]
+    fn reopen_keeps_a_healthy_port() {
+        let (app, window, _) = mock_session(b"");
+        block_on(reopen(app.handle(), window.clone(), app.state(), path())).unwrap();
+        assert!(is_open(app.handle(), window.clone(), app.state(), path()));
+        let result = block_on(reopen(
+            app.handle(),
+            window,
+            app.state(),
+            "/dev/none".into(),
+        ));
+        assert!(matches!(result, Err(Error::PortNotFound(_))));
+    }
+
+    #[test]
+    fn reset_statistics_clears_counters() {
+        let (app, window, _) = mock_session(b"");
+        write(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            "abcd".into(),
+        )
+        .unwrap();
+        reset_statistics(app.handle(), window.clone(), app.state(), path()).unwrap();
+        let stats = get_statistics(app.handle(), window, app.state(), path()).unwrap();
+        assert_eq!(stats.bytes_written, 0);
+        assert_eq!(stats.write_count, 0);
+    }
+
+    #[test]
+    fn send_break_succeeds() {
+        let (app, window, _) = mock_session(b"");
+        block_on(send_break(
+            app.handle(),
+            window,
+            app.state(),
+            path(),
+            Some(1),
+        ))
+        .unwrap();
+    }
+
+    #[test]
+    fn set_baud_rate_updates_the_config() {
+        let (app, window, _) = mock_session(b"");
+        set_baud_rate(app.handle(), window.clone(), app.state(), path(), 115_200).unwrap();
+        let config = get_port_settings(app.handle(), window, app.state(), path()).unwrap();
+        assert_eq!(config.baud_rate, 115_200);
+    }
+
+    #[test]
+    fn port_monitor_starts_and_stops() {
+        let (app, window, _) = mock_session(b"");
+        start_port_monitor(app.handle(), window.clone(), app.state(), Some(10)).unwrap();
+        // starting twice keeps the running monitor
+        start_port_monitor(app.handle(), window.clone(), app.state(), Some(10)).unwrap();
+        stop_port_monitor(app.handle(), window.clone(), app.state()).unwrap();
+        stop_port_monitor(app.handle(), window, app.state()).unwrap();
+    }
+
+    #[test]
+    fn supported_baud_rates_restores_the_baud_rate() {
+        let (app, window, mock) = mock_session(b"");
+        let rates = supported_baud_rates(app.handle(), window, app.state(), path()).unwrap();
+        assert!(rates.contains(&250_000));
+        assert_eq!(serialport::SerialPort::baud_rate(&mock).unwrap(), 9600);
+    }
+
+    #[test]
+    fn supported_baud_rates_refuses_a_port_being_read() {
+        let (app, window, _) = mock_session(b"");
+        collect_reads(
+            &app,
+            ReadOptions {
+                timeout: Some(10),
+                ..ReadOptions::default()
+            },
+        );
+        let result = supported_baud_rates(app.handle(), window.clone(), app.state(), path());
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+        block_on(cancel_read(app.handle(), window, app.state(), path())).unwrap();
+    }
+
+    #[test]
+    fn transaction_returns_the_response() {
+        let (app, window, mock) = mock_session(b"OK\r\nnext");
+        let response = block_on(transaction(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            b"AT\r\n".to_vec(),
+            100,
+            None,
+            Some(b"\r\n".to_vec()),
+        ))
+        .unwrap();
+        assert_eq!(response, b"OK\r\n");
+        assert_eq!(mock.written(), b"AT\r\n");
+        let result = block_on(transaction(
+            app.handle(),
+            window,
+            app.state(),
+            path(),
+            vec![],
+            50,
+            Some(8),
+            None,
+        ));
+        assert!(matches!(result, Err(Error::IoError(_))));
+    }
+
+    #[test]
+    fn update_settings_keeps_omitted_settings() {
+        let (app, window, _) = mock_session(b"");
+        if let Some(serialport_info) = app
+            .state::<SerialportState>()
+            .serialports
+            .lock()
+            .unwrap()
+            .get_mut(PATH)
+        {
+            serialport_info.config.inter_char_timeout_ms = Some(20);
+        }
+        update_settings(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            None,
+            Some(7),
+            None,
+            Some("even".into()),
+            Some(2),
+            None,
+        )
+        .unwrap();
+        let config = get_port_settings(app.handle(), window, app.state(), path()).unwrap();
+        assert_eq!(config.baud_rate, 9600);
+        assert_eq!(config.data_bits, 7);
+        assert_eq!(config.parity, "Even");
+        assert_eq!(config.stop_bits, 2);
+        assert_eq!(config.flow_control, "None");
+        assert_eq!(config.inter_char_timeout_ms, Some(20));
+    }
+
+    #[test]
+    fn write_sends_the_value_unchanged() {
+        let (app, window, mock) = mock_session(b"");
+        assert_eq!(
+            write(app.handle(), window, app.state(), path(), "AT".into()).unwrap(),
+            2
+        );
+        assert_eq!(mock.written(), b"AT");
+    }
+
+    #[test]
+    fn write_all_sends_everything() {
+        let (app, window, mock) = mock_session(b"");
+        write_all(
+            app.handle(),
+            window,
+            app.state(),
+            path(),
+            "hello".into(),
+            Some(1),
+        )
+        .unwrap();
+        assert_eq!(mock.written(), b"hello");
+    }
+
+    #[test]
+    fn write_base64_decodes_the_value() {
+        let (app, window, mock) = mock_session(b"");
+        write_base64(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            "AQL/".into(),
+        )
+        .unwrap();
+        assert_eq!(mock.written(), [0x01, 0x02, 0xff]);
+        let result = write_base64(app.handle(), window, app.state(), path(), "%%".into());
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn write_binary_sends_bytes() {
+        let (app, window, mock) = mock_session(b"");
+        write_binary(app.handle(), window, app.state(), path(), vec![0, 1, 2]).unwrap();
+        assert_eq!(mock.written(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn write_binary_all_sends_everything() {
+        let (app, window, mock) = mock_session(b"");
+        write_binary_all(app.handle(), window, app.state(), path(), vec![9; 64], None).unwrap();
+        assert_eq!(mock.written(), [9; 64]);
+    }
+
+    #[test]
+    fn write_binary_with_drain_sends_bytes() {
+        let (app, window, mock) = mock_session(b"");
+        write_binary_with_drain(app.handle(), window, app.state(), path(), vec![7, 8]).unwrap();
+        assert_eq!(mock.written(), [7, 8]);
+    }
+
+    #[test]
+    fn write_chunks_sends_everything_in_pieces() {
+        let (app, window, mock) = mock_session(b"");
+        let written = block_on(write_chunks(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            vec![1, 2, 3, 4, 5],
+            2,
+            1,
+        ))
+        .unwrap();
+        assert_eq!(written, 5);
+        assert_eq!(mock.written(), [1, 2, 3, 4, 5]);
+        let stats = get_statistics(app.handle(), window.clone(), app.state(), path()).unwrap();
+        assert_eq!(stats.write_count, 3);
+        let result = block_on(write_chunks(
+            app.handle(),
+            window,
+            app.state(),
+            path(),
+            vec![1],
+            0,
+            1,
+        ));
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn write_cobs_sends_a_delimited_frame() {
+        let (app, window, mock) = mock_session(b"");
+        let size = write_cobs(app.handle(), window, app.state(), path(), vec![0x11, 0x00]).unwrap();
+        assert_eq!(size, 4);
+        assert_eq!(mock.written(), [0x02, 0x11, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn write_data_terminal_ready_sets_the_signal() {
+        let (app, window, _) = mock_session(b"");
+        write_data_terminal_ready(app.handle(), window.clone(), app.state(), path(), false)
+            .unwrap();
+        assert!(!read_data_set_ready(app.handle(), window, app.state(), path()).unwrap());
+    }
+
+    #[test]
+    fn write_hex_decodes_the_value() {
+        let (app, window, mock) = mock_session(b"");
+        write_hex(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            "A5 01 ff".into(),
+        )
+        .unwrap();
+        assert_eq!(mock.written(), [0xa5, 0x01, 0xff]);
+        let result = write_hex(app.handle(), window, app.state(), path(), "abc".into());
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn write_line_appends_the_line_ending() {
+        let (app, window, mock) = mock_session(b"");
+        write_line(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            "AT".into(),
+            None,
+        )
+        .unwrap();
+        write_line(
+            app.handle(),
+            window,
+            app.state(),
+            path(),
+            "OK".into(),
+            Some("\n".into()),
+        )
+        .unwrap();
+        assert_eq!(mock.written(), b"AT\r\nOK\n");
+    }
+
+    #[test]
+    fn write_modbus_rtu_appends_the_crc() {
+        let (app, window, mock) = mock_session(b"");
+        let size = write_modbus_rtu(
+            app.handle(),
+            window,
+            app.state(),
+            path(),
+            0x01,
+            0x03,
+            vec![0x00, 0x00, 0x00, 0x0A],
+        )
+        .unwrap();
+        assert_eq!(size, 8);
+        assert_eq!(
+            mock.written(),
+            [0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD]
+        );
+    }
+
+    #[test]
+    fn write_queued_data_is_written_in_order() {
+        let (app, window, mock) = mock_session(b"");
+        for (value, delay) in [(vec![1, 2], Some(1)), (vec![3], None)] {
+            write_queued(
+                app.handle(),
+                window.clone(),
+                app.state(),
+                path(),
+                value,
+                delay,
+            )
+            .unwrap();
+        }
+        block_on(flush_write_queue(app.handle(), window, app.state(), path())).unwrap();
+        assert_eq!(mock.written(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn write_request_to_send_sets_the_signal() {
+        let (app, window, _) = mock_session(b"");
+        write_request_to_send(app.handle(), window.clone(), app.state(), path(), false).unwrap();
+        assert!(!read_clear_to_send(app.handle(), window, app.state(), path()).unwrap());
+    }
+
+    #[test]
+    fn write_with_crc_appends_the_checksum() {
+        let (app, window, mock) = mock_session(b"");
+        let size = write_with_crc(
+            app.handle(),
+            window.clone(),
+            app.state(),
+            path(),
+            b"123456789".to_vec(),
+            "crc16-ccitt".into(),
+        )
+        .unwrap();
+        assert_eq!(size, 11);
+        assert_eq!(mock.written(), b"123456789\x29\xB1");
+        let result = write_with_crc(
+            app.handle(),
+            window,
+            app.state(),
+            path(),
+            vec![],
+            "md5".into(),
+        );
+        assert!(matches!(result, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn write_with_drain_sends_the_value() {
+        let (app, window, mock) = mock_session(b"");
+        write_with_drain(app.handle(), window, app.state(), path(), "AT".into()).unwrap();
+        assert_eq!(mock.written(), b"AT");
+    }
+}
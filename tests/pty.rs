@@ -0,0 +1,27 @@
+#![cfg(all(feature = "pty", unix))]
+
+use std::io::{Read, Write};
+use std::time::Duration;
+use tauri_plugin_serialport::create_pty_pair;
+
+#[test]
+fn pty_pair_forwards_data_both_ways() {
+    let (path_a, path_b) = create_pty_pair().unwrap();
+    let mut port_a = serialport::new(&path_a, 115_200)
+        .timeout(Duration::from_secs(1))
+        .open()
+        .unwrap();
+    let mut port_b = serialport::new(&path_b, 115_200)
+        .timeout(Duration::from_secs(1))
+        .open()
+        .unwrap();
+
+    port_a.write_all(b"ping").unwrap();
+    let mut buf = [0; 4];
+    port_b.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ping");
+
+    port_b.write_all(b"pong").unwrap();
+    port_a.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"pong");
+}
@@ -7,14 +7,168 @@ export interface InvokeResult {
   message: string;
 }
 
+/** Error object rejected by the plugin commands */
+export interface SerialportError {
+  kind:
+    | 'PortNotFound'
+    | 'AlreadyOpen'
+    | 'IoError'
+    | 'LockPoisoned'
+    | 'InvalidArgument'
+    | 'AccessDenied'
+    | 'NoPortSelected'
+    | 'PortLeased'
+    | 'PermissionDenied'
+    | 'TooManyPorts'
+    | 'PartialWrite'
+    | 'Unsupported'
+    | 'SerialError';
+  message: string;
+}
+
 export interface ReadDataResult {
   size: number;
-  data: number[];
+  /** byte array, or a hex / base64 string when `readEncoding` is set */
+  data: number[] | string;
+  /** Unix time in milliseconds at which the data was read */
+  timestampMs: number;
+}
+
+export interface SerialPortDetail {
+  portName: string;
+  portType: 'USB' | 'PCI' | 'Bluetooth' | 'Unknown';
+  usbVid: number | null;
+  usbPid: number | null;
+  usbManufacturer: string | null;
+  usbSerialNumber: string | null;
+  usbProduct: string | null;
+  /** sysfs path of the USB device, Linux only */
+  devicePath: string | null;
+  /** name shown by Windows, e.g. `USB Serial Port (COM3)`, Windows only */
+  friendlyName: string | null;
+}
+
+/** Settings of one port of `Serialport.batchOpen` */
+export type OpenParams = Pick<
+  SerialportOptions,
+  | 'path'
+  | 'baudRate'
+  | 'dataBits'
+  | 'flowControl'
+  | 'parity'
+  | 'stopBits'
+  | 'timeout'
+  | 'idleTimeoutMs'
+  | 'accessMode'
+  | 'validateBaudRate'
+  | 'interCharTimeoutMs'
+  | 'initSequence'
+  | 'initDelayMs'
+>;
+
+/** Outcome of opening one port with `Serialport.batchOpen` */
+export type OpenResult = { Ok: null } | { Err: SerialportError };
+
+/** Narrows the ports offered by `requestPort`, a port matches if it has every field that is set */
+export interface PortFilter {
+  usbVendorId?: number;
+  usbProductId?: number;
+  /** part of the port name, e.g. "ttyUSB" */
+  namePattern?: string;
+}
+
+export interface SerialportConfig {
+  baudRate: number;
+  dataBits: 5 | 6 | 7 | 8;
+  parity: 'None' | 'Odd' | 'Even';
+  flowControl: 'None' | 'Software' | 'Hardware';
+  stopBits: 1 | 2;
+  timeoutMs: number;
+  interCharTimeoutMs: number | null;
+}
+
+/** Read and write statistics of an open port */
+export interface SerialportStats {
+  bytesRead: number;
+  readCount: number;
+  bytesWritten: number;
+  writeCount: number;
+  readErrors: number;
+  /** bytes dropped while `maxPendingEvents` read events were pending */
+  droppedBytes: number;
+  /** Unix time in milliseconds at which the port was opened */
+  openedAt: number;
+}
+
+/** Levels of the input control signals of a port */
+export interface SignalState {
+  clearToSend: boolean;
+  dataSetReady: boolean;
+  ringIndicator: boolean;
+  carrierDetect: boolean;
+}
+
+/** Settings, statistics and control signal levels of an open port */
+export interface PortInfo {
+  config: SerialportConfig;
+  stats: SerialportStats;
+  signals: SignalState;
+  isReading: boolean;
+  /** whether the `initSequence` was written when the port was opened */
+  initialized: boolean;
+}
+
+/** Payload of the `plugin-serialport-port-added`, `plugin-serialport-port-removed`, `plugin-serialport-port-opened` and `plugin-serialport-port-closed` events, the latter two are emitted to every window when any window opens or closes a port */
+export interface PortEvent {
+  portName: string;
+}
+
+/** Payload of the `plugin-serialport-reconnecting-{path}` event */
+export interface ReconnectData {
+  portName: string;
+  attempt: number;
+  delayMs: number;
+}
+
+/** Returned by `acquireLease` and passed to `releaseLease` */
+export interface LeaseToken {
+  path: string;
+  token: string;
+  windowLabel: string;
+  /** Unix time in ms at which the lease expires unless acquired again */
+  expiresAtMs: number;
+}
+
+/** Payload of the read event of a `read` with `nmea` framing, one per sentence */
+export interface NmeaData {
+  /** the sentence without line ending, e.g. "$GPGGA,...*47" */
+  sentence: string;
+  /** e.g. "GPGGA" */
+  sentenceType: string;
+  timestampMs: number;
+}
+
+/** Payload of the `plugin-serialport-overflow-{path}` event */
+export interface OverflowData {
+  portName: string;
+  droppedBytes: number;
+}
+
+/** Payload of the `plugin-serialport-silence-{path}` event */
+export interface SilenceData {
+  /** time since the last byte was received, or since the read started */
+  durationMs: number;
+}
+
+export interface ReadErrorResult {
+  message: string;
+  kind: string;
 }
 
 export interface SerialportOptions {
   path: string;
-  baudRate: number;
+  /** defaults to `defaultBaudRate` of the plugin configuration, or 9600 */
+  baudRate?: number;
   encoding?: string;
   dataBits?: 5 | 6 | 7 | 8;
   flowControl?: null | 'Software' | 'Hardware';
@@ -22,9 +176,32 @@ export interface SerialportOptions {
   stopBits?: 1 | 2;
   timeout?: number;
   size?: number;
+  /** close the port while reading once nothing was read or written for this many ms */
+  idleTimeoutMs?: number;
+  /** open the port for reading or writing only, reading and writing by default */
+  accessMode?: 'read_only' | 'write_only' | 'read_write';
+  /** reject non-standard baud rates, which are passed to the driver as they are by default */
+  validateBaudRate?: boolean;
+  /**
+   * return a read once no byte arrived for this many ms after the first one, rounded up to 100 ms on Linux and macOS.
+   * Changing `timeout` with `updateSettings` drops it on Windows
+   */
+  interCharTimeoutMs?: number;
+  /** bytes written as soon as the port is open, e.g. to set up a modem. The port is closed again if they cannot be written */
+  initSequence?: number[];
+  /** pause in ms after writing `initSequence` for the device to process it */
+  initDelayMs?: number;
   [key: string]: any;
 }
 
+/** Settings accepted by `updateSettings` */
+export type SerialportSettings = Partial<
+  Pick<
+    SerialportOptions,
+    'baudRate' | 'dataBits' | 'flowControl' | 'parity' | 'stopBits' | 'timeout'
+  >
+>;
+
 interface Options {
   dataBits: 5 | 6 | 7 | 8;
   flowControl: null | 'Software' | 'Hardware';
@@ -34,14 +211,68 @@ interface Options {
   [key: string]: any;
 }
 
+/** Checksum appended by `writeWithCrc` and checked by the `crc` option of `read` */
+export type CrcAlgorithm = 'crc8' | 'crc16-ccitt' | 'crc16-modbus' | 'crc32';
+
 interface ReadOptions {
+  /** longest pause in ms between two reads, shortened while data arrives, defaults to 200 */
   timeout?: number;
   size?: number;
+  readEncoding?: 'raw' | 'hex' | 'base64';
+  /** only emit data once this sequence is received, e.g. "\r\n" */
+  delimiter?: string | Uint8Array | number[];
+  /** only emit packets of exactly this many bytes, cannot be combined with `delimiter` */
+  packetSize?: number;
+  /** stop reading after this many consecutive read errors, defaults to 5 */
+  maxReadErrors?: number;
+  /** emit the data under this event instead of `plugin-serialport-read-{path}`, e.g. "gps-data" */
+  eventName?: string;
+  /** label of the window to emit the data to instead of the calling one */
+  targetWindow?: string;
+  /**
+   * decode the messages of this protocol, cannot be combined with `delimiter` or `packetSize`.
+   * Undecodable messages are reported as `plugin-serialport-framing-error-{path}` events.
+   * `nmea` emits each NMEA 0183 sentence as `NmeaData` and sentences with a wrong checksum as
+   * `plugin-serialport-nmea-error-{path}` events
+   */
+  framing?: 'slip' | 'cobs' | 'length_prefix' | 'modbus_rtu' | 'nmea';
+  /** size in bytes of the length header of `length_prefix` framing, defaults to 2 */
+  lengthFieldSize?: 1 | 2 | 4;
+  /** byte order of the length header of `length_prefix` framing, defaults to 'big' */
+  endian?: 'big' | 'little';
+  /**
+   * strip and check the checksum trailing every frame, sent most significant byte first, which needs `delimiter`, `packetSize` or `framing`.
+   * Frames that fail it are emitted as `plugin-serialport-crc-error-{path}` events
+   */
+  crc?: CrcAlgorithm;
+  /**
+   * emit the data received within this many ms of its first byte as a single event, which cannot be combined with `packetSize` or `framing`.
+   * Trades latency for fewer events on fast ports
+   */
+  batchMs?: number;
+  /**
+   * drop received data while this many read events are not acknowledged, reporting it as a `plugin-serialport-overflow-{path}` event.
+   * `listen` acknowledges every event once its handler returns
+   */
+  maxPendingEvents?: number;
+  /**
+   * emit `plugin-serialport-silence-{path}` with `SilenceData` once nothing was received for this many ms, e.g. to detect the gap ending a frame.
+   * Emitted again only after data arrived in between, and up to `timeout` late
+   */
+  silenceTimeoutMs?: number;
+  /**
+   * emit every frame split off by `delimiter` as its own event, instead of one event for all frames that arrived together.
+   * Other framing always emits one event per frame
+   */
+  emitPerPacket?: boolean;
 }
 
 class Serialport {
   isOpen: boolean;
   unListen?: UnlistenFn;
+  unListenError?: UnlistenFn;
+  readEvent?: string;
+  maxPendingEvents?: number;
   encoding: string;
   options: Options;
   size: number;
@@ -55,8 +286,12 @@ class Serialport {
       dataBits: options.dataBits || 8,
       flowControl: options.flowControl || null,
       parity: options.parity || null,
-      stopBits: options.stopBits || 2,
+      stopBits: options.stopBits || 1,
       timeout: options.timeout || 200,
+      idleTimeoutMs: options.idleTimeoutMs,
+      accessMode: options.accessMode,
+      validateBaudRate: options.validateBaudRate,
+      interCharTimeoutMs: options.interCharTimeoutMs,
     };
     this.size = options.size || 1024;
   }
@@ -73,6 +308,53 @@ class Serialport {
     }
   }
 
+  /**
+   * @description: Get serial port list with port type and USB metadata
+   * @return {Promise<SerialPortDetail[]>}
+   */
+  static async available_ports_info(): Promise<SerialPortDetail[]> {
+    try {
+      return await invoke<SerialPortDetail[]>(
+        'plugin:serialport|available_ports_info',
+      );
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the list of serial ports of one type
+   * @param {string} portType one of 'USB', 'Bluetooth', 'PCI' or 'Unknown', every port when omitted
+   * @return {Promise<string[]>}
+   */
+  static async available_ports_filtered(
+    portType?: SerialPortDetail['portType'],
+  ): Promise<string[]> {
+    try {
+      return await invoke<string[]>('plugin:serialport|available_ports_filtered', {
+        portType,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the name Windows shows for a port, e.g. `USB Serial Port (COM3)`, which `available_ports_info` also reports as `friendlyName`. Rejects with an `Unsupported` error on other platforms
+   * @param {string} portName e.g. `COM3`
+   * @return {Promise<string | null>} the friendly name, null if the port has none
+   */
+  static async windowsFriendlyName(portName: string): Promise<string | null> {
+    try {
+      return await invoke<string | null>(
+        'plugin:serialport|windows_friendly_name',
+        { portName },
+      );
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
   /**
    * @description: Force close serial port
    * @param {string} path
@@ -93,29 +375,45 @@ class Serialport {
   }
 
   /**
-   * @description: Cancel serial port monitoring
-   * @return {Promise<void>}
+   * @description: Open several serial ports, with `failFast` the first failure closes the ports opened so far and rejects, so either all ports are open or none
+   * @param {OpenParams[]} ports
+   * @param {boolean} failFast
+   * @return {Promise<OpenResult[]>} the outcome for each port
    */
-  async cancelListen(): Promise<void> {
+  static async batchOpen(
+    ports: OpenParams[],
+    failFast?: boolean,
+  ): Promise<OpenResult[]> {
     try {
-      if (this.unListen) {
-        this.unListen();
-        this.unListen = undefined;
-      }
-      return;
+      return await invoke<OpenResult[]>('plugin:serialport|batch_open', {
+        ports,
+        failFast,
+      });
     } catch (error) {
-      return Promise.reject('Error cancelling listen: ' + error);
+      return Promise.reject(error);
     }
   }
 
   /**
-   * @description: Cancel read data
-   * @return {Promise<void>}
+   * @description: Find the baud rate a device answers at by opening the closed port at common rates from 300 to 230400, writing `testPayload` and waiting `timeoutMs` for a response starting with `expectedResponsePrefix`
+   * @param {string} path
+   * @param {Uint8Array | number[]} testPayload
+   * @param {Uint8Array | number[]} expectedResponsePrefix
+   * @param {number} timeoutMs
+   * @return {Promise<number | null>} the first matching baud rate
    */
-  async cancelRead(): Promise<void> {
+  static async scanBaudRate(
+    path: string,
+    testPayload: Uint8Array | number[],
+    expectedResponsePrefix: Uint8Array | number[],
+    timeoutMs: number,
+  ): Promise<number | null> {
     try {
-      return await invoke<void>('plugin:serialport|cancel_read', {
-        path: this.options.path,
+      return await invoke<number | null>('plugin:serialport|scan_baud_rate', {
+        path,
+        testPayload: Array.from(testPayload),
+        expectedResponsePrefix: Array.from(expectedResponsePrefix),
+        timeoutMs,
       });
     } catch (error) {
       return Promise.reject(error);
@@ -123,127 +421,181 @@ class Serialport {
   }
 
   /**
-   * @description:
-   * @param {object} options
-   * @return {Promise<void>}
+   * @description: Close every serial port without reads or writes for `inactiveForMs`, e.g. periodically from a timer
+   * @param {number} inactiveForMs
+   * @return {Promise<string[]>} the paths of the closed ports
    */
-  async change(options: { path?: string; baudRate?: number }): Promise<void> {
+  static async closeInactivePorts(inactiveForMs: number): Promise<string[]> {
     try {
-      let isOpened = false;
-      if (this.isOpen) {
-        isOpened = true;
-        await this.close();
-      }
-      if (options.path) {
-        this.options.path = options.path;
-      }
-      if (options.baudRate) {
-        this.options.baudRate = options.baudRate;
-      }
-      if (isOpened) {
-        await this.open();
-      }
-      return Promise.resolve();
+      return await invoke<string[]>('plugin:serialport|close_inactive_ports', {
+        inactiveForMs,
+      });
     } catch (error) {
       return Promise.reject(error);
     }
   }
 
   /**
-   * @description: Close the serial port
-   * @return {Promise<InvokeResult>}
+   * @description: Create two connected virtual serial ports for testing without hardware, data written to one is read from the other. Open both like any other port. Needs the `pty` feature, only supported on Linux and macOS, rejects with an `Unsupported` error otherwise
+   * @return {Promise<[string, string]>} the paths of both ports
    */
-  async close(): Promise<void> {
+  static async createVirtualPortPair(): Promise<[string, string]> {
     try {
-      if (!this.isOpen) {
-        return;
-      }
-      await this.cancelRead();
-      const res = await invoke<void>('plugin:serialport|close', {
-        path: this.options.path,
+      return await invoke<[string, string]>(
+        'plugin:serialport|create_virtual_port_pair',
+      );
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Check whether a serial port is currently open
+   * @param {string} path
+   * @return {Promise<boolean>}
+   */
+  static async isPortOpen(path: string): Promise<boolean> {
+    return await invoke<boolean>('plugin:serialport|is_open', {
+      path,
+    });
+  }
+
+  /**
+   * @description: Get the list of currently open serial ports
+   * @return {Promise<string[]>}
+   */
+  static async listOpenPorts(): Promise<string[]> {
+    return await invoke<string[]>('plugin:serialport|list_open_ports');
+  }
+
+  /**
+   * @description: Open a serial port at 8 data bits, no parity, one stop bit, no flow control and a 200 ms timeout
+   * @param {string} path
+   * @param {number} baudRate
+   * @return {Promise<Serialport>} the opened port
+   */
+  static async openWithDefaults(
+    path: string,
+    baudRate: number,
+  ): Promise<Serialport> {
+    try {
+      await invoke<void>('plugin:serialport|open_with_defaults', {
+        path,
+        baudRate,
       });
+      const serialport = new Serialport({ path, baudRate });
+      serialport.isOpen = true;
+      return serialport;
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
 
-      await this.cancelListen();
-      this.isOpen = false;
-      return res;
+  /**
+   * @description: Open an RFCOMM channel of a Bluetooth serial (SPP) device directly instead of its virtual COM port, so the device does not need to be paired first. The port is registered as `bluetooth://{address}/{channel}` and passes bytes through unchanged. Rejects with an `Unsupported` error on platforms other than Linux and Windows
+   * @param {string} address device address, e.g. `00:11:22:AA:BB:CC`
+   * @param {number} channel RFCOMM channel from 1 to 30
+   * @param {number} baudRate only reported back by `getPortSettings`
+   * @param {number} timeout read timeout in ms, 200 by default
+   * @return {Promise<Serialport>} the opened port
+   */
+  static async openBluetooth(
+    address: string,
+    channel: number,
+    baudRate?: number,
+    timeout?: number,
+  ): Promise<Serialport> {
+    try {
+      await invoke<void>('plugin:serialport|open_bluetooth', {
+        address,
+        channel,
+        baudRate,
+        timeout,
+      });
+      const serialport = new Serialport({
+        path: `bluetooth://${address.replace(/-/g, ':').toUpperCase()}/${channel}`,
+        baudRate,
+        timeout,
+      });
+      serialport.isOpen = true;
+      return serialport;
     } catch (error) {
       return Promise.reject(error);
     }
   }
 
   /**
-   * @description: Listen to serial port
-   * @param {function} fn
-   * @return {Promise<void>}
+   * @description: Open a Windows named pipe in place of a serial port, e.g. QEMU's `-serial pipe:`. The port is registered as `\\.\pipe\{name}` and passes bytes through unchanged. Rejects on other platforms
+   * @param {string} name name of the pipe or its full path
+   * @param {number} baudRate only reported back by `getPortSettings`
+   * @param {number} timeout read timeout in ms, 200 by default
+   * @return {Promise<Serialport>} the opened port
    */
-  async listen(fn: (...args: any[]) => void, isDecode = true): Promise<void> {
+  static async openPipe(
+    name: string,
+    baudRate?: number,
+    timeout?: number,
+  ): Promise<Serialport> {
     try {
-      await this.cancelListen();
-      let readEvent = 'plugin-serialport-read-' + this.options.path;
-      this.unListen = await appWindow.listen<ReadDataResult>(
-        readEvent,
-        ({ payload }) => {
-          try {
-            if (isDecode) {
-              const decoder = new TextDecoder(this.encoding);
-              const data = decoder.decode(new Uint8Array(payload.data));
-              fn(data);
-            } else {
-              fn(new Uint8Array(payload.data));
-            }
-          } catch (error) {
-            console.error(error);
-          }
-        },
-      );
-      return;
+      await invoke<void>('plugin:serialport|open_pipe', {
+        name,
+        baudRate,
+        timeout,
+      });
+      const serialport = new Serialport({
+        path: name.startsWith('\\\\.\\pipe\\') ? name : `\\\\.\\pipe\\${name}`,
+        baudRate,
+        timeout,
+      });
+      serialport.isOpen = true;
+      return serialport;
     } catch (error) {
-      return Promise.reject('Error to listen: ' + error);
+      return Promise.reject(error);
     }
   }
 
   /**
-   * @description: Open serial port
-   * @return {*}
+   * @description: Open a serial port reached over a raw TCP connection, e.g. a serial device server. The port is registered as `tcp://{host}:{port}` and passes bytes through unchanged, so the line settings are configured on the server
+   * @param {string} host
+   * @param {number} port
+   * @param {number} baudRate only reported back by `getPortSettings`
+   * @param {number} timeout read timeout in ms, 200 by default
+   * @return {Promise<Serialport>} the opened port
    */
-  async open(): Promise<void> {
+  static async openTcp(
+    host: string,
+    port: number,
+    baudRate?: number,
+    timeout?: number,
+  ): Promise<Serialport> {
     try {
-      if (!this.options.path) {
-        return Promise.reject(`Path cannot be empty!`);
-      }
-      if (!this.options.baudRate) {
-        return Promise.reject(`Baudrate cannot be empty!`);
-      }
-      if (this.isOpen) {
-        return;
-      }
-      const res = await invoke<void>('plugin:serialport|open', {
-        path: this.options.path,
-        baudRate: this.options.baudRate,
-        dataBits: this.options.dataBits,
-        flowControl: this.options.flowControl,
-        parity: this.options.parity,
-        stopBits: this.options.stopBits,
-        timeout: this.options.timeout,
+      await invoke<void>('plugin:serialport|open_tcp', {
+        host,
+        port,
+        baudRate,
+        timeout,
       });
-      this.isOpen = true;
-      return Promise.resolve(res);
+      const serialport = new Serialport({
+        path: `tcp://${host}:${port}`,
+        baudRate,
+        timeout,
+      });
+      serialport.isOpen = true;
+      return serialport;
     } catch (error) {
       return Promise.reject(error);
     }
   }
 
   /**
-   * @description: Read serial port data
-   * @param {ReadOptions} options { timeout, size }
-   * @return {Promise<void>}
+   * @description: Let the user pick a serial port matching any of `filters`, like `navigator.serial.requestPort()`. Needs the `dialog` feature of the plugin to ask the user, otherwise the only matching port is returned. Rejects with a `NoPortSelected` error if none is picked
+   * @param {PortFilter[]} filters
+   * @return {Promise<string>} the path of the port
    */
-  async read(options?: ReadOptions): Promise<void> {
+  static async requestPort(filters?: PortFilter[]): Promise<string> {
     try {
-      return await invoke<void>('plugin:serialport|read', {
-        path: this.options.path,
-        timeout: options?.timeout || this.options.timeout,
-        size: options?.size || this.size,
+      return await invoke<string>('plugin:serialport|request_port', {
+        filters,
       });
     } catch (error) {
       return Promise.reject(error);
@@ -251,62 +603,42 @@ class Serialport {
   }
 
   /**
-   * @description: Set serial port baud rate
-   * @param {number} value
+   * @description: Start emitting `plugin-serialport-port-added` and `plugin-serialport-port-removed` events with a `PortEvent` payload when ports are plugged in or removed
+   * @param {number} intervalMs How often the port list is polled, 1000 by default
    * @return {Promise<void>}
    */
-  async setBaudRate(value: number): Promise<void> {
+  static async startPortMonitor(intervalMs?: number): Promise<void> {
     try {
-      let isOpened = false;
-      if (this.isOpen) {
-        isOpened = true;
-        await this.close();
-      }
-      this.options.baudRate = value;
-      if (isOpened) {
-        await this.open();
-      }
-      return Promise.resolve();
+      return await invoke<void>('plugin:serialport|start_port_monitor', {
+        intervalMs,
+      });
     } catch (error) {
       return Promise.reject(error);
     }
   }
 
   /**
-   * @description: Set serial port path
-   * @param {string} value
+   * @description: Stop the port monitor started by `startPortMonitor`
    * @return {Promise<void>}
    */
-  async setPath(value: string): Promise<void> {
+  static async stopPortMonitor(): Promise<void> {
     try {
-      let isOpened = false;
-      if (this.isOpen) {
-        isOpened = true;
-        await this.close();
-      }
-      this.options.path = value;
-      if (isOpened) {
-        await this.open();
-      }
-      return Promise.resolve();
+      return await invoke<void>('plugin:serialport|stop_port_monitor');
     } catch (error) {
       return Promise.reject(error);
     }
   }
 
   /**
-   * @description: Write data to serial port
-   * @param {string} value
-   * @return {Promise<number>}
+   * @description: Reserve the serial port for the current window, reads, writes and changes of its settings or control lines from other windows reject with a `PortLeased` error until the lease is released or expires
+   * @param {number} ttlMs lifetime of the lease, 30000 by default, acquire again to extend it
+   * @return {Promise<LeaseToken>}
    */
-  async write(value: string): Promise<number> {
+  async acquireLease(ttlMs?: number): Promise<LeaseToken> {
     try {
-      if (!this.isOpen) {
-        return Promise.reject(`Serial port ${this.options.path} is not opened!`);
-      }
-      return await invoke<number>('plugin:serialport|write', {
-        value,
+      return await invoke<LeaseToken>('plugin:serialport|acquire_lease', {
         path: this.options.path,
+        ttlMs,
       });
     } catch (error) {
       return Promise.reject(error);
@@ -314,29 +646,1282 @@ class Serialport {
   }
 
   /**
-   * @description: Write binary data to serial port
-   * @param {Uint8Array} value
-   * @return {Promise<number>}
+   * @description: Release a lease taken by `acquireLease`
+   * @param {LeaseToken} token
+   * @return {Promise<void>}
    */
-  async writeBinary(value: Uint8Array | number[]): Promise<number> {
+  async releaseLease(token: LeaseToken): Promise<void> {
     try {
-      if (!this.isOpen) {
-        return Promise.reject(`Serial port ${this.options.path} is not open!`);
-      }
-      if (value instanceof Uint8Array || value instanceof Array) {
+      return await invoke<void>('plugin:serialport|release_lease', {
+        token,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Send an AT command followed by "\r\n" and wait for the final `OK` or `ERROR` line, the echo of the command is removed
+   * @param {string} cmd e.g. "AT+CSQ"
+   * @param {number} timeoutMs
+   * @return {Promise<string>} the response, ending with `OK` or `ERROR`
+   */
+  async atCommand(cmd: string, timeoutMs: number): Promise<string> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<string>('plugin:serialport|at_command', {
+        path: this.options.path,
+        cmd,
+        timeoutMs,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Check that an AT device responds by sending `AT` and waiting for `OK`
+   * @param {number} timeoutMs defaults to 1000
+   * @return {Promise<void>}
+   */
+  async atInit(timeoutMs?: number): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<void>('plugin:serialport|at_init', {
+        path: this.options.path,
+        timeoutMs,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Mark read events as handled, only needed for reads started with `maxPendingEvents` and not received through `listen`
+   * @param {number} count number of events handled, defaults to 1
+   * @return {Promise<void>}
+   */
+  async acknowledgeRead(count?: number): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialport|acknowledge_read', {
+        path: this.options.path,
+        count,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the number of bytes waiting in the OS receive buffer
+   * @return {Promise<number>}
+   */
+  async bytesToRead(): Promise<number> {
+    try {
+      return await invoke<number>('plugin:serialport|bytes_to_read', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the number of bytes waiting in the OS transmit buffer
+   * @return {Promise<number>}
+   */
+  async bytesToWrite(): Promise<number> {
+    try {
+      return await invoke<number>('plugin:serialport|bytes_to_write', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Cancel serial port monitoring
+   * @return {Promise<void>}
+   */
+  async cancelListen(): Promise<void> {
+    try {
+      if (this.unListen) {
+        this.unListen();
+        this.unListen = undefined;
+      }
+      if (this.unListenError) {
+        this.unListenError();
+        this.unListenError = undefined;
+      }
+      return;
+    } catch (error) {
+      return Promise.reject('Error cancelling listen: ' + error);
+    }
+  }
+
+  /**
+   * @description: Cancel read data
+   * @return {Promise<void>}
+   */
+  async cancelRead(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialport|cancel_read', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description:
+   * @param {object} options
+   * @return {Promise<void>}
+   */
+  async change(options: { path?: string; baudRate?: number }): Promise<void> {
+    try {
+      let isOpened = false;
+      if (this.isOpen) {
+        isOpened = true;
+        await this.close();
+      }
+      if (options.path) {
+        this.options.path = options.path;
+      }
+      if (options.baudRate) {
+        this.options.baudRate = options.baudRate;
+      }
+      if (isOpened) {
+        await this.open();
+      }
+      return Promise.resolve();
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Discard bytes received but not yet read
+   * @return {Promise<void>}
+   */
+  async clearInputBuffer(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialport|clear_input_buffer', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Close the serial port
+   * @return {Promise<InvokeResult>}
+   */
+  async close(): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return;
+      }
+      await this.cancelRead();
+      const res = await invoke<void>('plugin:serialport|close', {
+        path: this.options.path,
+      });
+
+      await this.cancelListen();
+      this.isOpen = false;
+      return res;
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Wait until all buffered output has been transmitted
+   * @return {Promise<void>}
+   */
+  async flush(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialport|flush', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Wait until the data queued by writeQueued has been written and transmitted, failing with the first error a queued write ran into
+   * @return {Promise<void>}
+   */
+  async flushWriteQueue(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialport|flush_write_queue', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the settings the serial port was opened with
+   * @return {Promise<SerialportConfig>}
+   */
+  async getPortSettings(): Promise<SerialportConfig> {
+    try {
+      return await invoke<SerialportConfig>(
+        'plugin:serialport|get_port_settings',
+        {
+          path: this.options.path,
+        },
+      );
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the type of the serial port, as found when it was opened
+   * @return {Promise<'USB' | 'Bluetooth' | 'PCI' | 'Unknown'>}
+   */
+  async getPortType(): Promise<'USB' | 'Bluetooth' | 'PCI' | 'Unknown'> {
+    try {
+      return await invoke<'USB' | 'Bluetooth' | 'PCI' | 'Unknown'>(
+        'plugin:serialport|get_port_type',
+        {
+          path: this.options.path,
+        },
+      );
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the read and write statistics of the serial port
+   * @return {Promise<SerialportStats>}
+   */
+  async getStatistics(): Promise<SerialportStats> {
+    try {
+      return await invoke<SerialportStats>('plugin:serialport|get_statistics', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Listen to serial port, under the `eventName` of the last `read` if any
+   * @param {function} fn
+   * @return {Promise<void>}
+   */
+  async listen(fn: (...args: any[]) => void, isDecode = true): Promise<void> {
+    try {
+      await this.cancelListen();
+      let readEvent =
+        this.readEvent || 'plugin-serialport-read-' + this.options.path;
+      this.unListen = await appWindow.listen<ReadDataResult>(
+        readEvent,
+        ({ payload }) => {
+          try {
+            if (typeof payload.data === 'string') {
+              fn(payload.data);
+            } else if (isDecode) {
+              const decoder = new TextDecoder(this.encoding);
+              const data = decoder.decode(new Uint8Array(payload.data));
+              fn(data);
+            } else {
+              fn(new Uint8Array(payload.data));
+            }
+          } catch (error) {
+            console.error(error);
+          }
+          if (this.maxPendingEvents) {
+            this.acknowledgeRead().catch(console.error);
+          }
+        },
+      );
+      return;
+    } catch (error) {
+      return Promise.reject('Error to listen: ' + error);
+    }
+  }
+
+  /**
+   * @description: Listen to errors of the serial port read loop
+   * @param {function} fn
+   * @return {Promise<void>}
+   */
+  async listenError(fn: (error: ReadErrorResult) => void): Promise<void> {
+    try {
+      if (this.unListenError) {
+        this.unListenError();
+      }
+      const errorEvent = 'plugin-serialport-error-' + this.options.path;
+      this.unListenError = await appWindow.listen<ReadErrorResult>(
+        errorEvent,
+        ({ payload }) => fn(payload),
+      );
+      return;
+    } catch (error) {
+      return Promise.reject('Error to listen: ' + error);
+    }
+  }
+
+  /**
+   * @description: Open serial port
+   * @return {*}
+   */
+  async open(): Promise<void> {
+    try {
+      if (!this.options.path) {
+        return Promise.reject(`Path cannot be empty!`);
+      }
+      if (this.isOpen) {
+        return;
+      }
+      const res = await invoke<void>('plugin:serialport|open', {
+        path: this.options.path,
+        baudRate: this.options.baudRate,
+        dataBits: this.options.dataBits,
+        flowControl: this.options.flowControl,
+        parity: this.options.parity,
+        stopBits: this.options.stopBits,
+        timeout: this.options.timeout,
+        idleTimeoutMs: this.options.idleTimeoutMs,
+        accessMode: this.options.accessMode,
+        validateBaudRate: this.options.validateBaudRate,
+        interCharTimeoutMs: this.options.interCharTimeoutMs,
+        initSequence: this.options.initSequence,
+        initDelayMs: this.options.initDelayMs,
+      });
+      this.isOpen = true;
+      return Promise.resolve(res);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Open serial port and reopen it with exponential backoff when it is lost while being read,
+   * emitting `plugin-serialport-reconnecting-{path}` before each attempt and `plugin-serialport-reconnected-{path}` once one succeeds
+   * @param {number} maxRetries attempts made before giving up
+   * @param {number} baseDelayMs delay before the first attempt, doubled for each following one up to 30 s
+   * @return {Promise<void>}
+   */
+  async openWithReconnect(maxRetries: number, baseDelayMs: number): Promise<void> {
+    try {
+      if (!this.options.path) {
+        return Promise.reject(`Path cannot be empty!`);
+      }
+      if (this.isOpen) {
+        return;
+      }
+      const res = await invoke<void>('plugin:serialport|open_with_reconnect', {
+        path: this.options.path,
+        baudRate: this.options.baudRate,
+        dataBits: this.options.dataBits,
+        flowControl: this.options.flowControl,
+        parity: this.options.parity,
+        stopBits: this.options.stopBits,
+        timeout: this.options.timeout,
+        idleTimeoutMs: this.options.idleTimeoutMs,
+        accessMode: this.options.accessMode,
+        validateBaudRate: this.options.validateBaudRate,
+        interCharTimeoutMs: this.options.interCharTimeoutMs,
+        maxRetries,
+        baseDelayMs,
+      });
+      this.isOpen = true;
+      return Promise.resolve(res);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get up to `count` of the bytes waiting to be read without consuming them, so the next read returns them again. Rejects while `read` is running
+   * @param {number} count
+   * @return {Promise<number[]>}
+   */
+  async peek(count: number): Promise<number[]> {
+    try {
+      return await invoke<number[]>('plugin:serialport|peek', {
+        path: this.options.path,
+        count,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the settings, statistics and control signal levels of the serial port in one call
+   * @return {Promise<PortInfo>}
+   */
+  async portInfo(): Promise<PortInfo> {
+    try {
+      return await invoke<PortInfo>('plugin:serialport|port_info', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Read serial port data
+   * @param {ReadOptions} options { timeout, size, readEncoding, delimiter, packetSize, maxReadErrors, eventName, targetWindow, framing, lengthFieldSize, endian, crc, batchMs, maxPendingEvents, silenceTimeoutMs, emitPerPacket }
+   * @return {Promise<void>}
+   */
+  async read(options?: ReadOptions): Promise<void> {
+    try {
+      const delimiter =
+        typeof options?.delimiter === 'string'
+          ? Array.from(new TextEncoder().encode(options.delimiter))
+          : options?.delimiter && Array.from(options.delimiter);
+      this.readEvent = options?.eventName;
+      this.maxPendingEvents = options?.maxPendingEvents;
+      return await invoke<void>('plugin:serialport|read', {
+        path: this.options.path,
+        timeout: options?.timeout || this.options.timeout,
+        size: options?.size || this.size,
+        readEncoding: options?.readEncoding,
+        delimiter,
+        packetSize: options?.packetSize,
+        maxReadErrors: options?.maxReadErrors,
+        eventName: options?.eventName,
+        targetWindow: options?.targetWindow,
+        framing: options?.framing,
+        lengthFieldSize: options?.lengthFieldSize,
+        endian: options?.endian,
+        crc: options?.crc,
+        batchMs: options?.batchMs,
+        maxPendingEvents: options?.maxPendingEvents,
+        silenceTimeoutMs: options?.silenceTimeoutMs,
+        emitPerPacket: options?.emitPerPacket,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Read the state of the carrier detect (CD) control signal
+   * @return {Promise<boolean>}
+   */
+  async readCarrierDetect(): Promise<boolean> {
+    try {
+      return await invoke<boolean>('plugin:serialport|read_carrier_detect', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Read the state of the clear to send (CTS) control signal
+   * @return {Promise<boolean>}
+   */
+  async readClearToSend(): Promise<boolean> {
+    try {
+      return await invoke<boolean>('plugin:serialport|read_clear_to_send', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Read the state of the data set ready (DSR) control signal
+   * @return {Promise<boolean>}
+   */
+  async readDataSetReady(): Promise<boolean> {
+    try {
+      return await invoke<boolean>('plugin:serialport|read_data_set_ready', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Read and drop everything waiting in the receive buffer without emitting events, e.g. to resynchronize after a protocol error
+   * @return {Promise<number>} the number of bytes discarded
+   */
+  async readDiscard(): Promise<number> {
+    try {
+      return await invoke<number>('plugin:serialport|read_discard', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Read the state of the ring indicator (RI) control signal
+   * @return {Promise<boolean>}
+   */
+  async readRingIndicator(): Promise<boolean> {
+    try {
+      return await invoke<boolean>('plugin:serialport|read_ring_indicator', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Close and open the serial port again with the settings it was opened with, restarting the read loop if it was reading. Does nothing while the port still responds
+   * @return {Promise<void>}
+   */
+  async reopen(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialport|reopen', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Reset the read and write counters of the serial port
+   * @return {Promise<void>}
+   */
+  async resetStatistics(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialport|reset_statistics', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Assert the BREAK condition
+   * @param {number} durationMs break duration in milliseconds, defaults to 100
+   * @return {Promise<void>}
+   */
+  async sendBreak(durationMs?: number): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialport|send_break', {
+        path: this.options.path,
+        durationMs,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write the XOFF character (0x13) to pause the remote transmitter, regardless of the flow control setting
+   * @return {Promise<void>}
+   */
+  async sendXoff(): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<void>('plugin:serialport|send_xoff', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write the XON character (0x11) to resume the remote transmitter, regardless of the flow control setting
+   * @return {Promise<void>}
+   */
+  async sendXon(): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<void>('plugin:serialport|send_xon', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Set serial port baud rate
+   * @param {number} value
+   * @return {Promise<void>}
+   */
+  async setBaudRate(value: number): Promise<void> {
+    try {
+      if (this.isOpen) {
+        await invoke<void>('plugin:serialport|set_baud_rate', {
+          path: this.options.path,
+          baudRate: value,
+        });
+      }
+      this.options.baudRate = value;
+      return Promise.resolve();
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Log everything the port reads and writes as a hex dump, at the info level
+   * @param {boolean} enabled
+   * @return {Promise<void>}
+   */
+  async setHexDump(enabled: boolean): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<void>('plugin:serialport|set_hex_dump', {
+        path: this.options.path,
+        enabled,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Set serial port path
+   * @param {string} value
+   * @return {Promise<void>}
+   */
+  async setPath(value: string): Promise<void> {
+    try {
+      let isOpened = false;
+      if (this.isOpen) {
+        isOpened = true;
+        await this.close();
+      }
+      this.options.path = value;
+      if (isOpened) {
+        await this.open();
+      }
+      return Promise.resolve();
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the standard baud rates the driver of the serial port accepts, trying each of them on the port, which fails while it is being read or has a write queue
+   * @return {Promise<number[]>}
+   */
+  async supportedBaudRates(): Promise<number[]> {
+    try {
+      return await invoke<number[]>('plugin:serialport|supported_baud_rates', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write binary data and wait for the response, pausing a running read in the meantime
+   * @param {Uint8Array} payload
+   * @param {number} responseTimeoutMs time to wait for the response
+   * @param {object} options { responseSize, delimiter } where the response ends, everything received in time is returned without them
+   * @return {Promise<Uint8Array>} the response, including the delimiter
+   */
+  async transaction(
+    payload: Uint8Array | number[],
+    responseTimeoutMs: number,
+    options?: {
+      responseSize?: number;
+      delimiter?: string | Uint8Array | number[];
+    },
+  ): Promise<Uint8Array> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      const delimiter =
+        typeof options?.delimiter === 'string'
+          ? Array.from(new TextEncoder().encode(options.delimiter))
+          : options?.delimiter && Array.from(options.delimiter);
+      const response = await invoke<number[]>('plugin:serialport|transaction', {
+        path: this.options.path,
+        payload: Array.from(payload),
+        responseTimeoutMs,
+        responseSize: options?.responseSize,
+        delimiter,
+      });
+      return new Uint8Array(response);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Change the settings of the open serial port without closing it, settings left out keep their value
+   * @param {SerialportSettings} settings
+   * @return {Promise<void>}
+   */
+  async updateSettings(settings: SerialportSettings): Promise<void> {
+    try {
+      await invoke<void>('plugin:serialport|update_settings', {
+        path: this.options.path,
+        baudRate: settings.baudRate,
+        dataBits: settings.dataBits,
+        // `null` turns flow control or parity off, `undefined` keeps the current value
+        flowControl: settings.flowControl === null ? 'None' : settings.flowControl,
+        parity: settings.parity === null ? 'None' : settings.parity,
+        stopBits: settings.stopBits,
+        timeout: settings.timeout,
+      });
+      Object.assign(this.options, settings);
+      return Promise.resolve();
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write data to serial port, no line ending is appended. Rejects with a `PartialWrite` error if only part of it was written, use `writeAll` to retry until everything is written
+   * @param {string} value
+   * @return {Promise<number>}
+   */
+  async write(value: string): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not opened!`);
+      }
+      return await invoke<number>('plugin:serialport|write', {
+        value,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write data to serial port, retrying partial writes until every byte is written
+   * @param {string} value
+   * @param {number} maxAttempts maximum number of write calls, unlimited by default
+   * @return {Promise<void>}
+   */
+  async writeAll(value: string, maxAttempts?: number): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not opened!`);
+      }
+      return await invoke<void>('plugin:serialport|write_all', {
+        value,
+        path: this.options.path,
+        maxAttempts,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write a standard base64 encoded string to serial port as binary data
+   * @param {string} value
+   * @return {Promise<number>}
+   */
+  async writeBase64(value: string): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<number>('plugin:serialport|write_base64', {
+        value,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write binary data to serial port. Rejects with a `PartialWrite` error if only part of it was written, use `writeBinaryAll` to retry until everything is written
+   * @param {Uint8Array} value
+   * @return {Promise<number>}
+   */
+  async writeBinary(value: Uint8Array | number[]): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      if (value instanceof Uint8Array || value instanceof Array) {
         return await invoke<number>('plugin:serialport|write_binary', {
           value: Array.from(value),
           path: this.options.path,
         });
       } else {
         return Promise.reject(
-          'Argument type error! Expected type: string, Uint8Array, number[]',
+          'Argument type error! Expected type: string, Uint8Array, number[]',
+        );
+      }
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write binary data to serial port, retrying partial writes until every byte is written
+   * @param {Uint8Array} value
+   * @param {number} maxAttempts maximum number of write calls, unlimited by default
+   * @return {Promise<void>}
+   */
+  async writeBinaryAll(
+    value: Uint8Array | number[],
+    maxAttempts?: number,
+  ): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<void>('plugin:serialport|write_binary_all', {
+        value: Array.from(value),
+        path: this.options.path,
+        maxAttempts,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write `length` bytes of binary data starting at `offset`. Rejects with an `InvalidArgument` error if the range is out of bounds, and with a `PartialWrite` error like `writeBinary`
+   * @param {Uint8Array} value
+   * @param {number} offset
+   * @param {number} length
+   * @return {Promise<number>}
+   */
+  async writeBinarySlice(
+    value: Uint8Array | number[],
+    offset: number,
+    length: number,
+  ): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<number>('plugin:serialport|write_binary_slice', {
+        value: Array.from(value),
+        offset,
+        length,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write binary data to serial port and wait until it has been transmitted
+   * @param {Uint8Array} value
+   * @return {Promise<number>}
+   */
+  async writeBinaryWithDrain(value: Uint8Array | number[]): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<number>('plugin:serialport|write_binary_with_drain', {
+        value: Array.from(value),
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write binary data in pieces of `chunkSize` bytes, waiting `delayMs` between them, for receivers with small buffers
+   * @param {Uint8Array} value
+   * @param {number} chunkSize
+   * @param {number} delayMs
+   * @return {Promise<number>}
+   */
+  async writeChunks(
+    value: Uint8Array | number[],
+    chunkSize: number,
+    delayMs: number,
+  ): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<number>('plugin:serialport|write_chunks', {
+        path: this.options.path,
+        value: Array.from(value),
+        chunkSize,
+        delayMs,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: COBS-encode binary data and write it to serial port followed by a zero byte. Rejects with a `PartialWrite` error like `writeBinary`
+   * @param {Uint8Array} value
+   * @return {Promise<number>} number of encoded bytes written
+   */
+  async writeCobs(value: Uint8Array | number[]): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      if (value instanceof Uint8Array || value instanceof Array) {
+        return await invoke<number>('plugin:serialport|write_cobs', {
+          value: Array.from(value),
+          path: this.options.path,
+        });
+      } else {
+        return Promise.reject(
+          'Argument type error! Expected type: Uint8Array, number[]',
+        );
+      }
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Set the level of the data terminal ready (DTR) control signal
+   * @param {boolean} level
+   * @return {Promise<void>}
+   */
+  async writeDataTerminalReady(level: boolean): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialport|write_data_terminal_ready', {
+        path: this.options.path,
+        level,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write a hex encoded string such as "A5 01 FF" to serial port as binary data
+   * @param {string} value
+   * @return {Promise<number>}
+   */
+  async writeHex(value: string): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<number>('plugin:serialport|write_hex', {
+        value,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write the same byte `count` times, e.g. `0x55` sequences to reset a bus or enter a bootloader
+   * @param {number} byte
+   * @param {number} count
+   * @param {number} interByteDelayMs write the bytes one by one with this delay in between
+   * @return {Promise<number>}
+   */
+  async writeRepeated(
+    byte: number,
+    count: number,
+    interByteDelayMs?: number,
+  ): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<number>('plugin:serialport|write_repeated', {
+        path: this.options.path,
+        byte,
+        count,
+        interByteDelayMs,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write a single byte to serial port
+   * @param {number} value
+   * @return {Promise<void>}
+   */
+  async writeU8(value: number): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<void>('plugin:serialport|write_u8', {
+        value,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write a 16-bit unsigned integer to serial port, most significant byte first
+   * @param {number} value
+   * @return {Promise<void>}
+   */
+  async writeU16BE(value: number): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<void>('plugin:serialport|write_u16_be', {
+        value,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write a 16-bit unsigned integer to serial port, least significant byte first
+   * @param {number} value
+   * @return {Promise<void>}
+   */
+  async writeU16LE(value: number): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<void>('plugin:serialport|write_u16_le', {
+        value,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write a 32-bit unsigned integer to serial port, most significant byte first
+   * @param {number} value
+   * @return {Promise<void>}
+   */
+  async writeU32BE(value: number): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<void>('plugin:serialport|write_u32_be', {
+        value,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write a 32-bit unsigned integer to serial port, least significant byte first
+   * @param {number} value
+   * @return {Promise<void>}
+   */
+  async writeU32LE(value: number): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<void>('plugin:serialport|write_u32_le', {
+        value,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write data to serial port followed by a line ending
+   * @param {string} value
+   * @param {string} lineEnding defaults to "\r\n"
+   * @return {Promise<number>}
+   */
+  async writeLine(value: string, lineEnding?: string): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not opened!`);
+      }
+      return await invoke<number>('plugin:serialport|write_line', {
+        value,
+        path: this.options.path,
+        lineEnding,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write a MODBUS RTU request frame, the CRC-16 is appended. Rejects with a `PartialWrite` error like `writeBinary`
+   * @param {number} address
+   * @param {number} functionCode
+   * @param {Uint8Array} data
+   * @return {Promise<number>} size of the frame
+   */
+  async writeModbusRtu(
+    address: number,
+    functionCode: number,
+    data: Uint8Array | number[],
+  ): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<number>('plugin:serialport|write_modbus_rtu', {
+        path: this.options.path,
+        address,
+        functionCode,
+        data: Array.from(data),
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Queue binary data to be written in order by a background thread and return right away
+   * @param {Uint8Array} value
+   * @param {number} interByteDelayMs delay between bytes, none by default
+   * @return {Promise<number>} number of writes waiting, including this one
+   */
+  async writeQueued(
+    value: Uint8Array | number[],
+    interByteDelayMs?: number,
+  ): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<number>('plugin:serialport|write_queued', {
+        path: this.options.path,
+        value: Array.from(value),
+        interByteDelayMs,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Set the level of the request to send (RTS) control signal
+   * @param {boolean} level
+   * @return {Promise<void>}
+   */
+  async writeRequestToSend(level: boolean): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialport|write_request_to_send', {
+        path: this.options.path,
+        level,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write binary data to serial port followed by its checksum, most significant byte first. Rejects with a `PartialWrite` error like `writeBinary`
+   * @param {Uint8Array} value
+   * @param {CrcAlgorithm} algorithm
+   * @return {Promise<number>} number of bytes written including the checksum
+   */
+  async writeWithCrc(
+    value: Uint8Array | number[],
+    algorithm: CrcAlgorithm,
+  ): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      if (value instanceof Uint8Array || value instanceof Array) {
+        return await invoke<number>('plugin:serialport|write_with_crc', {
+          value: Array.from(value),
+          algorithm,
+          path: this.options.path,
+        });
+      } else {
+        return Promise.reject(
+          'Argument type error! Expected type: Uint8Array, number[]',
         );
       }
     } catch (error) {
       return Promise.reject(error);
     }
   }
+
+  /**
+   * @description: Write data to serial port and wait until it has been transmitted, e.g. before switching an RS-485 transceiver
+   * @param {string} value
+   * @return {Promise<number>}
+   */
+  async writeWithDrain(value: string): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<number>('plugin:serialport|write_with_drain', {
+        value,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write text to serial port in another charset than UTF-8, for devices expecting e.g. `é` as the single byte 0xE9. Characters missing from the charset are sent as `?`, or reject with an `InvalidArgument` error when `strict` is set
+   * @param {string} value
+   * @param {string} encoding IANA label such as `latin-1`, `windows-1252`, `ascii` or `utf-8`
+   * @param {boolean} strict
+   * @return {Promise<number>}
+   */
+  async writeWithEncoding(
+    value: string,
+    encoding: string,
+    strict?: boolean,
+  ): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`Serial port ${this.options.path} is not open!`);
+      }
+      return await invoke<number>('plugin:serialport|write_with_encoding', {
+        value,
+        encoding,
+        strict,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
 }
 
 export { Serialport };